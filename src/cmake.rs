@@ -0,0 +1,250 @@
+use crate::config::{Config, TargetKind};
+use crate::error::{Error, Result};
+use crate::graph::{self, Graph, Library, Node, Tool};
+use crate::project::{self, Language};
+use std::{env, fs, path::Path};
+
+const CMAKE_FILE: &str = "CMakeLists.txt";
+const HEADER: &str = "# Generated by tr2make from .tr2make";
+
+const LANGUAGES: &[(Language, &str)] = &[
+    (Language::C, "C"),
+    (Language::Cxx, "CXX"),
+    (Language::Asm, "ASM"),
+    (Language::Nasm, "ASM_NASM"),
+];
+
+pub fn export(config: &Config, force: bool) -> Result<()> {
+    let existing = fs::read_to_string(CMAKE_FILE).ok();
+    if existing.as_deref().is_some_and(|text| !text.starts_with(HEADER)) && !force {
+        return Err(Error::Usage {
+            message: format!("{CMAKE_FILE} already exists and was not generated by tr2make"),
+            help: Some("pass --force to overwrite it".to_string()),
+        });
+    }
+
+    let projects = config
+        .model_names()
+        .into_iter()
+        .map(|model| project::resolve(config, model))
+        .collect::<Result<Vec<_>>>()?;
+    let graphs: Vec<_> = projects.iter().map(graph::build).collect();
+    let content = render(config, &graphs);
+
+    if existing.is_some_and(|existing| existing == content) {
+        println!("{CMAKE_FILE} up to date");
+    } else {
+        fs::write(CMAKE_FILE, content).map_err(|e| Error::io(CMAKE_FILE, e))?;
+        println!("{CMAKE_FILE} generated ({} targets, {} build models)", projects[0].targets.len(), projects.len());
+    }
+    Ok(())
+}
+
+fn quote(value: &str) -> String {
+    if !value.is_empty() && !value.contains(|c: char| c.is_whitespace() || "\"\\;#()".contains(c)) {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace(';', "\\;"))
+    }
+}
+
+fn item(model: Option<&str>, language: Option<&str>, value: &str) -> String {
+    let mut value = match model.or(language) {
+        Some(_) => value.replace('>', "$<ANGLE-R>").replace(',', "$<COMMA>").replace(';', "$<SEMICOLON>"),
+        None => value.to_string(),
+    };
+    if let Some(language) = language {
+        value = format!("$<$<COMPILE_LANGUAGE:{language}>:{value}>");
+    }
+    if let Some(model) = model {
+        value = format!("$<$<CONFIG:{model}>:{value}>");
+    }
+    quote(&value)
+}
+
+// Values shared by every build model are emitted as is, the rest behind $<CONFIG:model>.
+fn per_model<'a>(graphs: &[Graph<'a>], values: impl Fn(&Graph) -> Vec<String>) -> Vec<(Option<&'a str>, String)> {
+    let values: Vec<_> = graphs.iter().map(|g| (g.project.model.as_str(), values(g))).collect();
+    let common: Vec<_> = values[0].1.iter().filter(|v| values.iter().all(|(_, vs)| vs.contains(v))).cloned().collect();
+
+    let mut items: Vec<_> = common.iter().map(|v| (None, v.clone())).collect();
+    for (model, vs) in &values {
+        items.extend(vs.iter().filter(|v| !common.contains(v)).map(|v| (Some(*model), v.clone())));
+    }
+    items
+}
+
+fn command(name: &str, target: &str, scope: &str, items: &[String]) -> String {
+    match items.is_empty() {
+        true => String::new(),
+        false => format!("{name}({target} {scope} {})\n", items.join(" ")),
+    }
+}
+
+fn uses(node: &Node, language: Language) -> bool {
+    node.compiles.iter().any(|c| c.language == language)
+}
+
+fn library(lib: &Library) -> String {
+    match lib {
+        Library::Flag(flag) => flag.clone(),
+        Library::Name(name) => name.clone(),
+        Library::File(path) if Path::new(path).is_absolute() => path.clone(),
+        Library::File(path) => format!("${{CMAKE_CURRENT_SOURCE_DIR}}/{path}"),
+    }
+}
+
+fn render(config: &Config, graphs: &[Graph]) -> String {
+    let project = graphs[0].project;
+    let used = |language| graphs[0].nodes.iter().any(|n| uses(n, language));
+    let languages: Vec<_> = LANGUAGES.iter().filter(|(language, _)| used(*language)).map(|(_, name)| *name).collect();
+    let name = env::current_dir()
+        .ok()
+        .and_then(|dir| dir.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| project.targets[0].name.clone());
+    let models: Vec<_> = graphs.iter().map(|g| g.project.model.as_str()).collect();
+
+    let mut out = format!("{HEADER}; edit .tr2make and rerun `tr2make export cmake` instead.\ncmake_minimum_required(VERSION 3.15)\n");
+    if project.assembler.as_deref() == Some("yasm") {
+        out.push_str("set(CMAKE_ASM_NASM_COMPILER yasm)\n");
+    }
+    out.push_str(&format!("project({} LANGUAGES {})\n\n", quote(&name), languages.join(" ")));
+
+    for (std, language) in [(&project.c_std, "C"), (&project.cxx_std, "CXX")] {
+        let Some(std) = std.as_ref().filter(|_| languages.contains(&language)) else { continue };
+        let version = match std.trim_start_matches(|c: char| c.is_alphabetic() || c == '+') {
+            "89" | "90" => "90",
+            "18" => "17",
+            "03" => "98",
+            version => version,
+        };
+        out.push_str(&format!(
+            "set(CMAKE_{language}_STANDARD {version})\nset(CMAKE_{language}_STANDARD_REQUIRED ON)\nset(CMAKE_{language}_EXTENSIONS OFF)\n\n"
+        ));
+    }
+
+    out.push_str(&format!(r#"set(CMAKE_CONFIGURATION_TYPES {models})
+if(NOT CMAKE_BUILD_TYPE)
+  set(CMAKE_BUILD_TYPE {default} CACHE STRING "Build model" FORCE)
+endif()
+set_property(CACHE CMAKE_BUILD_TYPE PROPERTY STRINGS {models})
+
+"#,
+        models = models.iter().map(|m| quote(m)).collect::<Vec<_>>().join(" "),
+        default = if models.contains(&"debug") { "debug" } else { models[0] },
+    ));
+    for model in graphs.iter().map(|g| g.project) {
+        for language in &languages {
+            let flags = if *language == "ASM_NASM" { String::new() } else { model.optimization.join(" ") };
+            out.push_str(&format!("set(CMAKE_{language}_FLAGS_{} \"{flags}\")\n", model.model.to_uppercase()));
+        }
+    }
+
+    if !project.march.is_empty() {
+        let compile_language = used(Language::Nasm).then_some("C,CXX,ASM");
+        let flags: Vec<_> = project.march.split_whitespace().collect();
+        out.push_str(&format!(
+            "\nadd_compile_options({})\nadd_link_options({})\n",
+            flags.iter().map(|f| item(None, compile_language, f)).collect::<Vec<_>>().join(" "),
+            flags.iter().map(|f| quote(f)).collect::<Vec<_>>().join(" "),
+        ));
+    }
+
+    for index in 0..graphs[0].nodes.len() {
+        out.push_str(&render_target(config, graphs, index));
+    }
+    out
+}
+
+fn render_target(config: &Config, graphs: &[Graph], index: usize) -> String {
+    let node = &graphs[0].nodes[index];
+    let target = node.target;
+    let name = &target.name;
+    let values = |f: &dyn Fn(&Node) -> Vec<String>| per_model(graphs, |g| f(&g.nodes[index]));
+    let render = |items: Vec<(Option<&str>, String)>, language: Option<&str>| -> Vec<String> {
+        items.iter().map(|(model, value)| item(*model, language, value)).collect()
+    };
+
+    let sources = target.sources.iter().map(|s| format!("  {}\n", quote(&s.path))).collect::<String>();
+    let mut out = match target.kind {
+        TargetKind::Executable => format!("\nadd_executable({name}\n{sources})\n"),
+        TargetKind::Static => format!("\nadd_library({name} STATIC\n{sources})\n"),
+        TargetKind::Shared => format!("\nadd_library({name} SHARED\n{sources})\n"),
+    };
+
+    let mut properties = Vec::new();
+    if let Some(output) = name.strip_prefix("lib").filter(|_| target.kind != TargetKind::Executable) {
+        properties.push(format!("OUTPUT_NAME {}", quote(output)));
+    }
+    if target.kind == TargetKind::Static {
+        properties.push("POSITION_INDEPENDENT_CODE ON".to_string());
+    }
+    if !properties.is_empty() {
+        out.push_str(&format!("set_target_properties({name} PROPERTIES {})\n", properties.join(" ")));
+    }
+
+    let public: Vec<_> = config
+        .targets
+        .get(name)
+        .map(|t| t.public_include_dirs.iter().map(|dir| project::normalize_dir(dir)).collect())
+        .unwrap_or_default();
+    let private = values(&|n| n.include_dirs.iter().filter(|dir| !public.contains(dir)).cloned().collect());
+    let mut includes = Vec::new();
+    if !public.is_empty() {
+        includes.push(format!("PUBLIC {}", public.iter().map(|dir| quote(dir)).collect::<Vec<_>>().join(" ")));
+    }
+    if !private.is_empty() {
+        includes.push(format!("PRIVATE {}", render(private, None).join(" ")));
+    }
+    if !includes.is_empty() {
+        out.push_str(&format!("target_include_directories({name} {})\n", includes.join(" ")));
+    }
+
+    let defines = values(&|n| {
+        n.target.flags.defines
+            .iter()
+            .map(|(name, value)| match value {
+                Some(value) => format!("{name}={value}"),
+                None => name.clone(),
+            })
+            .collect()
+    });
+    out.push_str(&command("target_compile_definitions", name, "PRIVATE", &render(defines, None)));
+
+    let has_asm = uses(node, Language::Asm) || uses(node, Language::Nasm);
+    let mut options = render(values(&|n| n.target.flags.cflags.clone()), has_asm.then_some("C,CXX"));
+    if has_asm {
+        options.extend(render(values(&|n| n.target.flags.asflags.clone()), Some("ASM,ASM_NASM")));
+    }
+    out.push_str(&command("target_compile_options", name, "PRIVATE", &options));
+
+    if node.link.tool != Tool::Ar {
+        let lib_dirs = values(&|n| n.target.flags.lib_dirs.iter().map(|dir| project::normalize_dir(dir)).collect());
+        out.push_str(&command("target_link_directories", name, "PRIVATE", &render(lib_dirs, None)));
+        out.push_str(&command("target_link_options", name, "PRIVATE", &render(values(&|n| n.target.flags.ldflags.clone()), None)));
+
+        let mut libs: Vec<_> = target.link.iter().map(|dep| quote(dep)).collect();
+        libs.extend(render(
+            values(&|n| {
+                n.libraries
+                    .iter()
+                    .map(library)
+                    .chain(n.target.flags.link_flags.iter().cloned())
+                    .chain(n.target.flags.frameworks.iter().map(|f| format!("-framework {f}")))
+                    .collect()
+            }),
+            None,
+        ));
+        out.push_str(&command("target_link_libraries", name, "PRIVATE", &libs));
+    }
+
+    let order_only: Vec<_> = node.depends_on
+        .iter()
+        .filter(|dep| !target.link.contains(&dep.name))
+        .map(|dep| quote(&dep.name))
+        .collect();
+    if !order_only.is_empty() {
+        out.push_str(&format!("add_dependencies({name} {})\n", order_only.join(" ")));
+    }
+    out
+}
@@ -0,0 +1,371 @@
+use serde::de::{self, MapAccess, Visitor, value::MapAccessDeserializer};
+use serde::{Deserialize, Deserializer};
+use std::{env, fmt};
+use serde_yaml::{Mapping, Number, Value};
+use std::collections::BTreeMap;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub language: String,
+    pub standard: Number,
+    pub c_standard: Option<Number>,
+    pub cxx_standard: Option<Number>,
+    #[serde(default)]
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub target: Option<String>,
+    #[serde(rename = "type", default)]
+    pub kind: TargetKind,
+    #[serde(default)]
+    pub targets: BTreeMap<String, TargetConfig>,
+    pub architecture: String,
+    pub assembler: Option<String>,
+    pub cross: Option<Cross>,
+    pub platform: Option<Platform>,
+    pub toolchain: Option<Toolchain>,
+    #[serde(skip)]
+    pub toolchain_override: Option<String>,
+    #[serde(skip)]
+    pub platform_override: Option<Platform>,
+    #[serde(skip)]
+    pub check_toolchain: bool,
+    #[serde(skip)]
+    pub backend: Backend,
+    #[serde(default)]
+    pub include_dirs: Vec<String>,
+    #[serde(default)]
+    pub defines: Defines,
+    #[serde(default)]
+    pub cflags: Vec<String>,
+    #[serde(default)]
+    pub asflags: Vec<String>,
+    #[serde(default)]
+    pub ldflags: Vec<String>,
+    #[serde(default)]
+    pub lib_dirs: Vec<String>,
+    #[serde(default)]
+    pub libs: Vec<String>,
+    #[serde(default)]
+    pub frameworks: Vec<String>,
+    #[serde(default)]
+    pub link_flags: Vec<String>,
+    #[serde(default)]
+    pub model: BTreeMap<String, BuildConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TargetConfig {
+    #[serde(rename = "type", default)]
+    pub kind: TargetKind,
+    #[serde(default)]
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub public_include_dirs: Vec<String>,
+    #[serde(default)]
+    pub include_dirs: Vec<String>,
+    #[serde(default)]
+    pub defines: Defines,
+    #[serde(default)]
+    pub cflags: Vec<String>,
+    #[serde(default)]
+    pub asflags: Vec<String>,
+    #[serde(default)]
+    pub ldflags: Vec<String>,
+    #[serde(default)]
+    pub lib_dirs: Vec<String>,
+    #[serde(default)]
+    pub libs: Vec<String>,
+    #[serde(default)]
+    pub frameworks: Vec<String>,
+    #[serde(default)]
+    pub link_flags: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetKind {
+    #[default]
+    Executable,
+    Static,
+    Shared,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Cross {
+    pub triple: Option<String>,
+    pub prefix: Option<String>,
+    pub sysroot: Option<String>,
+    #[serde(default)]
+    pub triples: BTreeMap<String, CrossOverride>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CrossOverride {
+    pub prefix: Option<String>,
+    pub sysroot: Option<String>,
+    #[serde(default)]
+    pub cflags: Vec<String>,
+    #[serde(default)]
+    pub ldflags: Vec<String>,
+}
+
+pub enum Toolchain {
+    Name(String),
+    Custom(CustomToolchain),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomToolchain {
+    pub cc: Option<String>,
+    pub cxx: Option<String>,
+    pub ar: Option<String>,
+    pub ld: Option<String>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Backend {
+    #[default]
+    Make,
+    Ninja,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Platform {
+    Linux,
+    WindowsMingw,
+    WindowsCmd,
+    Macos,
+    Freebsd,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct BuildConfig {
+    pub inherits: Option<String>,
+    pub targetdir: Option<String>,
+    pub optimization: Option<Vec<String>>,
+    #[serde(default)]
+    pub include_dirs: Vec<String>,
+    #[serde(default)]
+    pub defines: Defines,
+    #[serde(default)]
+    pub cflags: Vec<String>,
+    #[serde(default)]
+    pub asflags: Vec<String>,
+    #[serde(default)]
+    pub ldflags: Vec<String>,
+    #[serde(default)]
+    pub lib_dirs: Vec<String>,
+    #[serde(default)]
+    pub libs: Vec<String>,
+    #[serde(default)]
+    pub frameworks: Vec<String>,
+    #[serde(default)]
+    pub link_flags: Vec<String>,
+}
+
+#[derive(Clone, Default)]
+pub struct Flags {
+    pub include_dirs: Vec<String>,
+    pub defines: Vec<(String, Option<String>)>,
+    pub cflags: Vec<String>,
+    pub asflags: Vec<String>,
+    pub ldflags: Vec<String>,
+    pub lib_dirs: Vec<String>,
+    pub libs: Vec<String>,
+    pub frameworks: Vec<String>,
+    pub link_flags: Vec<String>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(untagged)]
+pub enum Defines {
+    List(Vec<String>),
+    Map(Mapping),
+}
+
+impl Default for Defines {
+    fn default() -> Self {
+        Defines::List(Vec::new())
+    }
+}
+
+impl Defines {
+    pub fn entries(&self) -> Vec<(String, Option<String>)> {
+        match self {
+            Defines::List(list) => list
+                .iter()
+                .map(|d| match d.split_once('=') {
+                    Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                    None => (d.clone(), None),
+                })
+                .collect(),
+            Defines::Map(map) => map
+                .iter()
+                .map(|(name, value)| (scalar(name).unwrap_or_default(), scalar(value)))
+                .collect(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Toolchain {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ToolchainVisitor;
+
+        impl<'de> Visitor<'de> for ToolchainVisitor {
+            type Value = Toolchain;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a toolchain name or a map of cc/cxx/ar/ld")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<Toolchain, E> {
+                Ok(Toolchain::Name(name.to_string()))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Toolchain, A::Error> {
+                CustomToolchain::deserialize(MapAccessDeserializer::new(map)).map(Toolchain::Custom)
+            }
+        }
+
+        deserializer.deserialize_any(ToolchainVisitor)
+    }
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(if *b { "1" } else { "0" }.to_string()),
+        _ => None,
+    }
+}
+
+impl Config {
+    pub fn model_names(&self) -> Vec<&str> {
+        let mut names: Vec<_> = ["debug", "release"].into_iter().chain(self.model.keys().map(String::as_str)).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+impl Platform {
+    pub const ALL: [(&str, Platform); 5] = [
+        ("linux", Platform::Linux),
+        ("windows-mingw", Platform::WindowsMingw),
+        ("windows-cmd", Platform::WindowsCmd),
+        ("macos", Platform::Macos),
+        ("freebsd", Platform::Freebsd),
+    ];
+
+    pub fn host() -> Platform {
+        match env::consts::OS {
+            "windows" => Platform::WindowsCmd,
+            "macos" => Platform::Macos,
+            "freebsd" => Platform::Freebsd,
+            _ => Platform::Linux,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Platform> {
+        Self::ALL.iter().find(|(n, _)| *n == name).map(|(_, platform)| *platform)
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::ALL.iter().find(|(_, p)| *p == self).map(|(name, _)| *name).unwrap()
+    }
+
+    pub fn is_windows(self) -> bool {
+        matches!(self, Platform::WindowsMingw | Platform::WindowsCmd)
+    }
+}
+
+impl BuildConfig {
+    pub fn builtin(name: &str) -> Option<BuildConfig> {
+        match name {
+            "debug" => Some(BuildConfig {
+                optimization: Some(vec!["-g".to_string(), "-O0".to_string()]),
+                defines: Defines::List(vec!["DEBUG".to_string()]),
+                ..Default::default()
+            }),
+            "release" => Some(BuildConfig {
+                optimization: Some(vec!["-O2".to_string()]),
+                ..Default::default()
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Flags {
+    pub fn extend(&mut self, other: Flags) {
+        for (name, value) in other.defines {
+            self.defines.retain(|(n, _)| *n != name);
+            self.defines.push((name, value));
+        }
+        self.include_dirs.extend(other.include_dirs);
+        self.cflags.extend(other.cflags);
+        self.asflags.extend(other.asflags);
+        self.ldflags.extend(other.ldflags);
+        self.lib_dirs.extend(other.lib_dirs);
+        self.libs.extend(other.libs);
+        self.frameworks.extend(other.frameworks);
+        self.link_flags.extend(other.link_flags);
+    }
+
+    pub fn describe(&self) -> Vec<(&'static str, String)> {
+        let defines = self.defines.iter().map(|(name, value)| match value {
+            Some(value) => format!("{name}={value}"),
+            None => name.clone(),
+        });
+
+        [
+            ("include_dirs", &self.include_dirs),
+            ("cflags", &self.cflags),
+            ("asflags", &self.asflags),
+            ("ldflags", &self.ldflags),
+            ("lib_dirs", &self.lib_dirs),
+            ("libs", &self.libs),
+            ("frameworks", &self.frameworks),
+            ("link_flags", &self.link_flags),
+        ]
+        .into_iter()
+        .flat_map(|(key, values)| values.iter().map(move |v| (key, v.clone())))
+        .chain(defines.map(|d| ("defines", d)))
+        .collect()
+    }
+}
+
+macro_rules! impl_flags {
+    ($($ty:ty),*) => {$(
+        impl $ty {
+            pub fn flags(&self) -> Flags {
+                Flags {
+                    include_dirs: self.include_dirs.clone(),
+                    defines: self.defines.entries(),
+                    cflags: self.cflags.clone(),
+                    asflags: self.asflags.clone(),
+                    ldflags: self.ldflags.clone(),
+                    lib_dirs: self.lib_dirs.clone(),
+                    libs: self.libs.clone(),
+                    frameworks: self.frameworks.clone(),
+                    link_flags: self.link_flags.clone(),
+                }
+            }
+        }
+    )*};
+}
+
+impl_flags!(Config, BuildConfig, TargetConfig);
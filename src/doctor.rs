@@ -0,0 +1,145 @@
+use crate::config::{Backend, Config};
+use crate::driver;
+use crate::error::{Error, Result};
+use crate::project::{self, Language, Project};
+use std::io::{self, ErrorKind};
+use std::process::{Command, Stdio};
+
+pub struct Check {
+    pub label: String,
+    pub outcome: Result<String>,
+    pub is_tool: bool,
+}
+
+fn command(tool: &str) -> Command {
+    let mut parts = tool.split_whitespace();
+    let mut command = Command::new(parts.next().unwrap_or(tool));
+    command.args(parts);
+    command
+}
+
+fn first_line(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).lines().next().unwrap_or_default().trim().to_string()
+}
+
+fn version(tool: &str, arg: &str) -> io::Result<String> {
+    let output = command(tool).arg(arg).stdin(Stdio::null()).output()?;
+    let text = if output.stdout.is_empty() { &output.stderr } else { &output.stdout };
+    Ok(first_line(text))
+}
+
+fn accepts(tool: &str, language: &str, flag: &str) -> io::Result<std::result::Result<(), String>> {
+    let output = command(tool)
+        .args(flag.split_whitespace())
+        .args(["-fsyntax-only", "-x", language, "-"])
+        .stdin(Stdio::null())
+        .output()?;
+    Ok(if output.status.success() { Ok(()) } else { Err(first_line(&output.stderr)) })
+}
+
+fn missing(project: &Project, tool: &str, key: &str, err: io::Error) -> Error {
+    let message = match err.kind() {
+        ErrorKind::NotFound => format!("`{tool}` was not found"),
+        _ => format!("`{tool}` could not be run: {err}"),
+    };
+    let help = match project.triple {
+        Some(_) => "install the cross toolchain, or point `cross.prefix` or `cross.triples` at an installed one",
+        None => "install it, or pick another compiler with `toolchain:`, --toolchain or CC/CXX/AR",
+    };
+    Error::config(message, &[key]).with_help(help)
+}
+
+fn probe_tool(checks: &mut Vec<Check>, project: &Project, tool: &str, key: &str, arg: &str) -> bool {
+    let outcome = version(tool, arg).map_err(|e| missing(project, tool, key, e));
+    let found = outcome.is_ok();
+    checks.push(Check { label: tool.to_string(), outcome, is_tool: true });
+    found
+}
+
+fn probe_flag(checks: &mut Vec<Check>, project: &Project, tool: &str, language: &str, flag: &str, key: &str, help: &str) {
+    let outcome = match accepts(tool, language, flag) {
+        Ok(Ok(())) => Ok("accepted".to_string()),
+        Ok(Err(reason)) => Err(Error::config(format!("`{tool}` does not accept {flag}: {reason}"), &[key]).with_help(help)),
+        Err(err) => Err(missing(project, tool, "toolchain", err)),
+    };
+    checks.push(Check { label: format!("{tool} {flag}"), outcome, is_tool: false });
+}
+
+pub fn checks(config: &Config, project: &Project) -> Vec<Check> {
+    let uses = |language| project.targets.iter().any(|t| t.sources.iter().any(|s| s.language == language));
+    let links = |language| project.targets.iter().any(|t| t.linker == language);
+    let compilers = [
+        (Language::C, &project.cc, "c", &project.c_std, if config.c_standard.is_some() { "c_standard" } else { "standard" }),
+        (Language::Cxx, &project.cxx, "c++", &project.cxx_std, if config.cxx_standard.is_some() { "cxx_standard" } else { "standard" }),
+    ];
+
+    let mut checks = Vec::new();
+    for (language, tool, name, std, key) in compilers {
+        let drives_asm = language == Language::C && uses(Language::Asm);
+        if !(uses(language) || links(language) || drives_asm) || !probe_tool(&mut checks, project, tool, "toolchain", "--version") {
+            continue;
+        }
+        if let Some(std) = std.as_ref().filter(|_| uses(language)) {
+            probe_flag(&mut checks, project, tool, name, &format!("-std={std}"), key, "upgrade the compiler or choose an older standard");
+        }
+        if project.march.is_empty() {
+            continue;
+        }
+        probe_flag(
+            &mut checks,
+            project,
+            tool,
+            name,
+            &project.march,
+            "architecture",
+            &format!("install a compiler that targets {}, or change `architecture`", project.architecture),
+        );
+    }
+
+    probe_tool(&mut checks, project, &project.ar, "toolchain", "--version");
+    if let Some(assembler) = project.assembler.as_deref().filter(|_| uses(Language::Nasm)) {
+        probe_tool(&mut checks, project, assembler, "assembler", "-v");
+    }
+    checks
+}
+
+pub fn verify(config: &Config, project: &mut Project) -> Result<()> {
+    for check in checks(config, project) {
+        let version = check.outcome?;
+        if check.is_tool {
+            project.versions.push((check.label, version));
+        }
+    }
+    Ok(())
+}
+
+pub fn doctor(config: &Config, model: &str, source: Option<&str>) -> Result<()> {
+    let project = project::resolve(config, model)?;
+    let mut checks = checks(config, &project);
+    let tool = driver::build_tool(config.backend);
+    let outcome = version(&tool, "--version").map_err(|_| Error::Usage {
+        message: format!("`{tool}` was not found"),
+        help: Some(format!("install it or point the {} variable at it", if config.backend == Backend::Ninja { "NINJA" } else { "MAKE" })),
+    });
+    checks.push(Check { label: tool, outcome, is_tool: true });
+
+    println!("Toolchain for model `{model}`:");
+    let mut problems = Vec::new();
+    for check in checks {
+        match check.outcome {
+            Ok(detail) => println!("  ok    {}: {detail}", check.label),
+            Err(err) => {
+                println!("  fail  {}", check.label);
+                problems.push(err);
+            }
+        }
+    }
+    for problem in &problems {
+        eprintln!("{}", problem.render(source));
+    }
+
+    match problems.len() {
+        0 => Ok(()),
+        count => Err(Error::Unavailable(format!("{count} toolchain problem{} found", if count == 1 { "" } else { "s" }))),
+    }
+}
@@ -0,0 +1,113 @@
+use crate::config::{Backend, Config, TargetKind};
+use crate::error::{Error, Result};
+use crate::doctor;
+use crate::graph::{self, Generator, shell_quote};
+use crate::makefile::Make;
+use crate::ninja::Ninja;
+use crate::project::{self, Project};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::{env, fs, thread};
+
+pub fn generate(config: &Config, model: &str) -> Result<(Project, PathBuf)> {
+    let mut project = project::resolve(config, model)?;
+    if config.check_toolchain {
+        doctor::verify(config, &mut project)?;
+    }
+    fs::create_dir_all(&project.build_dir).map_err(|e| Error::io(&project.build_dir, e))?;
+
+    let generator: Box<dyn Generator> = match config.backend {
+        Backend::Make => Box::new(Make),
+        Backend::Ninja => Box::new(Ninja { regenerate: regenerate_command(config, &project) }),
+    };
+    let file = generator.file_name();
+    let content = generator.render(&graph::build(&project));
+    let path = Path::new(&project.build_dir).join(file);
+    if fs::read_to_string(&path).is_ok_and(|existing| existing == content) {
+        println!("{file} up to date: {}", path.display());
+    } else {
+        fs::write(&path, content).map_err(|e| Error::io(path.display(), e))?;
+        println!("{file} generated at: {}", path.display());
+    }
+    Ok((project, path))
+}
+
+fn regenerate_command(config: &Config, project: &Project) -> String {
+    let exe = env::current_exe().map_or_else(|_| "tr2make".to_string(), |path| path.display().to_string());
+    let mut args = vec![exe, project.model.clone()];
+    args.extend(["--backend".to_string(), "ninja".to_string()]);
+    if let Some(platform) = config.platform_override {
+        args.extend(["--platform".to_string(), platform.name().to_string()]);
+    }
+    if let Some(toolchain) = &config.toolchain_override {
+        args.extend(["--toolchain".to_string(), toolchain.clone()]);
+    }
+    if config.check_toolchain {
+        args.push("--check-toolchain".to_string());
+    }
+    args.iter().map(|arg| shell_quote(project.platform, arg)).collect::<Vec<_>>().join(" ")
+}
+
+pub fn build_tool(backend: Backend) -> String {
+    match backend {
+        Backend::Make => env::var("MAKE").unwrap_or_else(|_| "make".to_string()),
+        Backend::Ninja => env::var("NINJA").unwrap_or_else(|_| "ninja".to_string()),
+    }
+}
+
+fn run_backend(config: &Config, build_file: &Path, args: &[String]) -> Result<()> {
+    let program = build_tool(config.backend);
+    let mut command = Command::new(&program);
+    match config.backend {
+        Backend::Make => command.arg("-f").arg(build_file),
+        Backend::Ninja => command.arg("-C").arg(build_file.parent().unwrap_or(Path::new("."))),
+    };
+    let status = command.args(args).status().map_err(|e| Error::io(&program, e))?;
+
+    match status.code() {
+        Some(0) => Ok(()),
+        code => Err(Error::Child { command: program, code: code.unwrap_or(1) }),
+    }
+}
+
+fn jobs_arg(jobs: Option<usize>) -> String {
+    let jobs = jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    format!("-j{jobs}")
+}
+
+pub fn build(config: &Config, model: &str, jobs: Option<usize>) -> Result<Project> {
+    let (project, build_file) = generate(config, model)?;
+    run_backend(config, &build_file, &[jobs_arg(jobs)])?;
+    Ok(project)
+}
+
+pub fn clean(config: &Config, model: &str) -> Result<()> {
+    let (_, build_file) = generate(config, model)?;
+    run_backend(config, &build_file, &["clean".to_string()])
+}
+
+pub fn run(config: &Config, model: &str, jobs: Option<usize>, target: Option<&str>, args: &[String]) -> Result<()> {
+    let project = build(config, model, jobs)?;
+
+    let executables: Vec<_> = project.targets.iter().filter(|t| t.kind == TargetKind::Executable).collect();
+    let names = || executables.iter().map(|t| t.name.as_str());
+    let executable = match target {
+        Some(name) => executables.iter().find(|t| t.name == name).ok_or_else(|| {
+            Error::Usage { message: format!("no executable target named `{name}`"), help: None }.suggest(name, names())
+        })?,
+        None => match executables.as_slice() {
+            [only] => only,
+            [] => return Err(Error::Usage { message: "the project has no executable target".to_string(), help: None }),
+            _ => {
+                return Err(Error::Usage {
+                    message: "the project has several executable targets".to_string(),
+                    help: Some(format!("pick one with --target: {}", names().collect::<Vec<_>>().join(", "))),
+                });
+            }
+        },
+    };
+
+    let path = Path::new(&project.build_dir).join(&executable.output);
+    let status = Command::new(&path).args(args).status().map_err(|e| Error::io(path.display(), e))?;
+    process::exit(status.code().unwrap_or(1));
+}
@@ -0,0 +1,180 @@
+use std::{fmt, io};
+
+pub const CONFIG_FILE: &str = ".tr2make";
+
+pub enum Error {
+    MissingConfig,
+    Io { path: String, source: io::Error },
+    Syntax(serde_yaml::Error),
+    Config { message: String, key: Vec<String>, help: Option<String> },
+    Usage { message: String, help: Option<String> },
+    Child { command: String, code: i32 },
+    Unavailable(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn config(message: impl Into<String>, key: &[&str]) -> Self {
+        Error::Config {
+            message: message.into(),
+            key: key.iter().map(|k| k.to_string()).collect(),
+            help: None,
+        }
+    }
+
+    pub fn io(path: impl fmt::Display, source: io::Error) -> Self {
+        Error::Io { path: path.to_string(), source }
+    }
+
+    pub fn with_help(mut self, text: impl Into<String>) -> Self {
+        if let Error::Config { help, .. } | Error::Usage { help, .. } = &mut self {
+            *help = Some(text.into());
+        }
+        self
+    }
+
+    pub fn suggest<'a>(self, value: &str, candidates: impl IntoIterator<Item = &'a str>) -> Self {
+        match suggestion(value, candidates) {
+            Some(candidate) => self.with_help(format!("did you mean `{candidate}`?")),
+            None => self,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Usage { .. } => 64,
+            Error::Syntax(_) | Error::Config { .. } => 65,
+            Error::MissingConfig => 66,
+            Error::Io { .. } => 74,
+            Error::Child { code, .. } => *code,
+            Error::Unavailable(_) => 69,
+        }
+    }
+
+    pub fn render(&self, source: Option<&str>) -> String {
+        let (message, location, help) = match self {
+            Error::MissingConfig => (
+                format!("no {CONFIG_FILE} found in the current directory"),
+                None,
+                Some(format!("create a {CONFIG_FILE} describing the project")),
+            ),
+            Error::Io { path, source } => (format!("{path}: {source}"), None, None),
+            Error::Syntax(err) => {
+                let message = strip_positions(&err.to_string());
+                let location = err.location().map(|l| (l.line(), l.column()));
+                match unknown_name_help(&message) {
+                    Some(help) => (message[..message.find(", expected").unwrap()].to_string(), location, Some(help)),
+                    None => (message, location, None),
+                }
+            }
+            Error::Config { message, key, help } => {
+                let location = source.and_then(|s| locate(s, key));
+                (message.clone(), location, help.clone())
+            }
+            Error::Usage { message, help } => (message.clone(), None, help.clone()),
+            Error::Child { command, code } => (format!("`{command}` exited with status {code}"), None, None),
+            Error::Unavailable(message) => (message.clone(), None, None),
+        };
+
+        let mut out = format!("error: {message}");
+        if let Some((line, column)) = location {
+            out.push_str(&format!("\n --> {CONFIG_FILE}:{line}:{column}"));
+            if let Some(text) = source.and_then(|s| s.lines().nth(line - 1)) {
+                let gutter = " ".repeat(line.to_string().len());
+                out.push_str(&format!(
+                    "\n{gutter} |\n{line} | {text}\n{gutter} | {}^",
+                    " ".repeat(column.saturating_sub(1))
+                ));
+            }
+        }
+        if let Some(help) = help {
+            out.push_str(&format!("\nhelp: {help}"));
+        }
+        out
+    }
+}
+
+pub fn suggestion<'a>(value: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .into_iter()
+        .map(|c| (strsim::jaro_winkler(value, c), c))
+        .filter(|(score, _)| *score > 0.7)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, c)| c)
+}
+
+fn unknown_name_help(message: &str) -> Option<String> {
+    let rest = message
+        .split_once("unknown field `")
+        .or_else(|| message.split_once("unknown variant `"))?
+        .1;
+    let (name, expected) = rest.split_once('`')?;
+    let expected = expected.strip_prefix(", expected")?;
+    let candidates: Vec<_> = expected.split('`').skip(1).step_by(2).collect();
+    if candidates.is_empty() {
+        return None;
+    }
+
+    Some(match suggestion(name, candidates.iter().copied()) {
+        Some(candidate) => format!("did you mean `{candidate}`?"),
+        None => format!("expected one of {}", candidates.iter().map(|c| format!("`{c}`")).collect::<Vec<_>>().join(", ")),
+    })
+}
+
+fn strip_positions(message: &str) -> String {
+    let mut out = String::new();
+    let mut rest = message;
+    while let Some(i) = rest.find(" at line ") {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i + " at line ".len()..];
+        let end = tail
+            .find(" column ")
+            .map(|c| c + " column ".len())
+            .map(|c| c + tail[c..].find(|ch: char| !ch.is_ascii_digit()).unwrap_or(tail.len() - c))
+            .unwrap_or(0);
+        rest = &tail[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn locate(source: &str, key: &[String]) -> Option<(usize, usize)> {
+    let mut found = None;
+    let mut parent_indent: Option<usize> = None;
+    let mut lines = source.lines().enumerate();
+
+    for part in key {
+        let mut level = None;
+        let mut hit = None;
+        for (number, line) in lines.by_ref() {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let indent = line.len() - trimmed.len();
+            if parent_indent.is_some_and(|p| indent <= p) {
+                break;
+            }
+            if *level.get_or_insert(indent) != indent {
+                continue;
+            }
+            let (name, value) = trimmed.split_once(':').unwrap_or((trimmed, ""));
+            if name.trim().trim_matches(['"', '\'']) == part {
+                let value = value.trim_start();
+                let column = if value.is_empty() { indent + 1 } else { line.len() - value.len() + 1 };
+                hit = Some((number + 1, column, indent));
+                break;
+            }
+        }
+
+        match hit {
+            Some((line, column, indent)) => {
+                found = Some((line, column));
+                parent_indent = Some(indent);
+            }
+            None => break,
+        }
+    }
+    found
+}
@@ -0,0 +1,212 @@
+use crate::error::{Error, Result};
+use std::collections::BTreeSet;
+use std::{fs, io, path::Path};
+
+pub fn resolve(entries: &[String], exclude: &[String], key: &[&str]) -> Result<Vec<String>> {
+    let mut files = BTreeSet::new();
+
+    for entry in entries {
+        let entry = normalize(entry);
+        if is_pattern(&entry) {
+            let base = literal_prefix(&entry);
+            let mut found = Vec::new();
+            walk(Path::new(if base.is_empty() { "." } else { &base }), &mut found)?;
+            files.extend(found.into_iter().filter(|f| matches(&entry, f)));
+        } else if Path::new(&entry).is_dir() {
+            let mut found = Vec::new();
+            walk(Path::new(&entry), &mut found)?;
+            files.extend(found);
+        } else if Path::new(&entry).is_file() {
+            files.insert(entry);
+        } else {
+            let dir = entry.rsplit_once('/').map_or(".", |(dir, _)| dir);
+            let mut siblings = Vec::new();
+            walk(Path::new(dir), &mut siblings)?;
+            return Err(Error::config(format!("source file `{entry}` does not exist"), key)
+                .suggest(&entry, siblings.iter().map(String::as_str)));
+        }
+    }
+
+    let exclude: Vec<_> = exclude.iter().map(|e| normalize(e)).collect();
+    Ok(files
+        .into_iter()
+        .filter(|f| !exclude.iter().any(|e| excluded(e, f)))
+        .collect())
+}
+
+fn walk(dir: &Path, out: &mut Vec<String>) -> Result<()> {
+    let mut entries = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .collect::<io::Result<Vec<_>>>()
+            .map_err(|e| Error::io(dir.display(), e))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(Error::io(dir.display(), e)),
+    };
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let file_type = entry.file_type().map_err(|e| Error::io(path.display(), e))?;
+        if file_type.is_dir() {
+            if !entry.file_name().to_string_lossy().starts_with('.') {
+                walk(&path, out)?;
+            }
+        } else {
+            out.push(normalize(&path.to_string_lossy()));
+        }
+    }
+    Ok(())
+}
+
+fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut path = path.as_str();
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest;
+    }
+    path.trim_end_matches('/').to_string()
+}
+
+fn is_pattern(entry: &str) -> bool {
+    entry.contains(['*', '?', '['])
+}
+
+fn literal_prefix(pattern: &str) -> String {
+    let mut parts: Vec<_> = pattern.split('/').collect();
+    parts.pop();
+    parts
+        .into_iter()
+        .take_while(|p| !is_pattern(p))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn excluded(pattern: &str, file: &str) -> bool {
+    if is_pattern(pattern) {
+        matches(pattern, file) || matches(&format!("{pattern}/**"), file)
+    } else {
+        file == pattern || file.starts_with(&format!("{pattern}/"))
+    }
+}
+
+fn matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<_> = pattern.split('/').collect();
+    let path: Vec<_> = path.split('/').collect();
+    match_components(&pattern, &path)
+}
+
+fn match_components(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|i| match_components(rest, &path[i..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                match_name(first.as_bytes(), name.as_bytes()) && match_components(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn match_name(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|i| match_name(rest, &name[i..])),
+        Some((b'?', rest)) => !name.is_empty() && match_name(rest, &name[1..]),
+        Some((b'[', rest)) => match (name.split_first(), class_end(rest)) {
+            (Some((&c, name_rest)), Some(end)) => {
+                match_class(&rest[..end], c) && match_name(&rest[end + 1..], name_rest)
+            }
+            (Some((&c, name_rest)), None) => c == b'[' && match_name(rest, name_rest),
+            (None, _) => false,
+        },
+        Some((&p, rest)) => name.first() == Some(&p) && match_name(rest, &name[1..]),
+    }
+}
+
+fn class_end(class: &[u8]) -> Option<usize> {
+    let skip = match class.first() {
+        Some(b'!') | Some(b'^') => 2,
+        _ => 1,
+    };
+    class.iter().skip(skip).position(|&c| c == b']').map(|i| i + skip)
+}
+
+fn match_class(class: &[u8], c: u8) -> bool {
+    let (negate, class) = match class.split_first() {
+        Some((b'!', rest)) | Some((b'^', rest)) => (true, rest),
+        _ => (false, class),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == b'-' {
+            found |= class[i] <= c && c <= class[i + 2];
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+    found != negate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_stays_within_one_component() {
+        assert!(matches("src/*.c", "src/main.c"));
+        assert!(matches("src/*", "src/main.c"));
+        assert!(!matches("src/*.c", "src/net/socket.c"));
+        assert!(!matches("src/*.c", "src/main.cpp"));
+    }
+
+    #[test]
+    fn double_star_spans_directories() {
+        assert!(matches("src/**/*.c", "src/main.c"));
+        assert!(matches("src/**/*.c", "src/net/tcp/socket.c"));
+        assert!(matches("**/*.c", "main.c"));
+        assert!(!matches("src/**/*.c", "lib/main.c"));
+    }
+
+    #[test]
+    fn question_mark_matches_one_character() {
+        assert!(matches("v?.c", "v1.c"));
+        assert!(!matches("v?.c", "v.c"));
+        assert!(!matches("v?.c", "v10.c"));
+    }
+
+    #[test]
+    fn character_classes() {
+        assert!(matches("v[0-9].c", "v7.c"));
+        assert!(!matches("v[0-9].c", "vx.c"));
+        assert!(matches("[abc].c", "b.c"));
+        assert!(matches("v[!0-9].c", "vx.c"));
+        assert!(!matches("v[^0-9].c", "v7.c"));
+        assert!(matches("[]].c", "].c"));
+        assert!(matches("a[.c", "a[.c"));
+    }
+
+    #[test]
+    fn literal_prefix_stops_at_first_pattern() {
+        assert_eq!(literal_prefix("src/net/*.c"), "src/net");
+        assert_eq!(literal_prefix("src/**/*.c"), "src");
+        assert_eq!(literal_prefix("*.c"), "");
+    }
+
+    #[test]
+    fn exclude_matches_directories_and_patterns() {
+        assert!(excluded("src/test", "src/test/main.c"));
+        assert!(!excluded("src/test", "src/testing.c"));
+        assert!(excluded("src/*_test.c", "src/net_test.c"));
+        assert!(excluded("src/gen*", "src/generated/table.c"));
+    }
+
+    #[test]
+    fn normalize_strips_dot_prefix_and_trailing_slash() {
+        assert_eq!(normalize("./src/"), "src");
+        assert_eq!(normalize(".\\src\\main.c"), "src/main.c");
+    }
+}
@@ -0,0 +1,423 @@
+use crate::config::{Platform, TargetKind};
+use crate::project::{Language, Project, Target, normalize_dir};
+
+pub trait Generator {
+    fn file_name(&self) -> &'static str;
+    fn render(&self, graph: &Graph) -> String;
+}
+
+#[derive(Clone)]
+pub enum Arg {
+    Literal(String),
+    Raw(String),
+    Rooted { prefix: &'static str, path: String },
+    Built { prefix: &'static str, path: String },
+    Var { prefix: &'static str, name: &'static str, target: Option<String> },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tool {
+    Cc,
+    Cxx,
+    Ar,
+    As,
+}
+
+pub struct Compile {
+    pub language: Language,
+    pub tool: Tool,
+    pub var: &'static str,
+    pub flags: Vec<Arg>,
+}
+
+pub struct Link {
+    pub tool: Tool,
+    pub flags: Vec<Arg>,
+    pub libs: Vec<Arg>,
+}
+
+pub enum Library {
+    Flag(String),
+    File(String),
+    Name(String),
+}
+
+pub struct Node<'a> {
+    pub target: &'a Target,
+    pub include_dirs: Vec<String>,
+    pub libraries: Vec<Library>,
+    pub includes: Vec<Arg>,
+    pub defines: Vec<Arg>,
+    pub compiles: Vec<Compile>,
+    pub link: Link,
+    pub depends_on: Vec<&'a Target>,
+}
+
+pub struct Graph<'a> {
+    pub project: &'a Project,
+    pub comments: Vec<String>,
+    pub variables: Vec<(&'static str, Arg)>,
+    pub nodes: Vec<Node<'a>>,
+}
+
+impl Tool {
+    pub fn var(self) -> &'static str {
+        match self {
+            Tool::Cc => "cc",
+            Tool::Cxx => "cxx",
+            Tool::Ar => "ar",
+            Tool::As => "as",
+        }
+    }
+}
+
+impl Arg {
+    fn var(prefix: &'static str, name: &'static str) -> Arg {
+        Arg::Var { prefix, name, target: None }
+    }
+
+    fn target_var(name: &'static str, target: &str) -> Arg {
+        Arg::Var { prefix: "", name, target: Some(target.to_string()) }
+    }
+
+    fn rooted(prefix: &'static str, path: &str) -> Arg {
+        Arg::Rooted { prefix, path: normalize_dir(path) }
+    }
+}
+
+impl Library {
+    pub fn classify(lib: &str) -> Library {
+        let is_file = [".a", ".so", ".lib", ".dylib"].iter().any(|ext| lib.ends_with(ext));
+        if lib.starts_with('-') {
+            Library::Flag(lib.to_string())
+        } else if lib.contains(['/', '\\']) || is_file {
+            Library::File(normalize_dir(lib))
+        } else {
+            Library::Name(lib.to_string())
+        }
+    }
+}
+
+pub fn shell_quote(platform: Platform, arg: &str) -> String {
+    if platform == Platform::WindowsCmd {
+        if arg.is_empty() || arg.contains([' ', '\t', '"', '&', '|', '<', '>', '^']) {
+            format!("\"{}\"", arg.replace('"', "\\\""))
+        } else {
+            arg.to_string()
+        }
+    } else if arg.is_empty() || arg.contains(|c: char| !c.is_ascii_alphanumeric() && !"-_=+,./:@%".contains(c)) {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    } else {
+        arg.to_string()
+    }
+}
+
+fn literals<'a>(prefix: &str, values: impl IntoIterator<Item = &'a String>) -> Vec<Arg> {
+    values.into_iter().map(|v| Arg::Literal(format!("{prefix}{v}"))).collect()
+}
+
+pub fn build(project: &Project) -> Graph<'_> {
+    let mut comments = vec![format!("Build model: {}", project.model_chain.join(" <- "))];
+    comments.extend(project.model_origins.iter().map(|(key, value, model)| format!("  {key}: {value} (from {model})")));
+    if !project.versions.is_empty() {
+        comments.push("Toolchain:".to_string());
+        comments.extend(project.versions.iter().map(|(tool, version)| format!("  {tool}: {version}")));
+    }
+
+    let mut variables = vec![
+        ("cc", Arg::Raw(project.cc.clone())),
+        ("cxx", Arg::Raw(project.cxx.clone())),
+        ("ar", Arg::Raw(project.ar.clone())),
+    ];
+    if let Some(std) = &project.c_std {
+        variables.push(("cstd", Arg::Literal(std.clone())));
+    }
+    if let Some(std) = &project.cxx_std {
+        variables.push(("cxxstd", Arg::Literal(std.clone())));
+    }
+    if let Some(assembler) = &project.assembler {
+        variables.push(("as", Arg::Raw(assembler.clone())));
+        variables.push(("asformat", Arg::Literal(project.asm_format.to_string())));
+    }
+    variables.push(("platform", Arg::Literal(project.platform.name().to_string())));
+    variables.push(("arch", Arg::Literal(project.architecture.clone())));
+    if let Some(triple) = &project.triple {
+        variables.push(("triple", Arg::Literal(triple.clone())));
+    }
+    if let Some(sysroot) = &project.sysroot {
+        variables.push(("sysroot", Arg::rooted("", sysroot)));
+    }
+    variables.push(("model", Arg::Literal(project.model.clone())));
+
+    let nodes = project.targets.iter().map(|target| node(project, target)).collect();
+    Graph { project, comments, variables, nodes }
+}
+
+fn node<'a>(project: &'a Project, target: &'a Target) -> Node<'a> {
+    let name = &target.name;
+    let flags = &target.flags;
+    let platform = project.platform;
+    let windows = platform.is_windows();
+    let macos = platform == Platform::Macos;
+
+    let defines: Vec<_> = flags.defines
+        .iter()
+        .map(|(name, value)| match value {
+            Some(value) => Arg::Literal(format!("-D{name}={value}")),
+            None => Arg::Literal(format!("-D{name}")),
+        })
+        .collect();
+    let include_dirs: Vec<_> = flags.include_dirs.iter().map(|dir| normalize_dir(dir)).collect();
+    let includes: Vec<_> = include_dirs.iter().map(|dir| Arg::rooted("-I", dir)).collect();
+
+    let march: Vec<_> = project.march.split_whitespace().map(|f| Arg::Literal(f.to_string())).collect();
+    let sysroot = project.sysroot.as_ref().map(|_| Arg::var("--sysroot=", "sysroot"));
+    let pic = (target.kind != TargetKind::Executable && !windows).then(|| Arg::Literal("-fPIC".to_string()));
+    let compile_flags = |std: Option<Arg>, extra: &[String]| {
+        let mut args = literals("", &project.optimization);
+        args.extend(std);
+        args.extend(march.iter().cloned());
+        args.extend(sysroot.clone());
+        args.extend(pic.clone());
+        args.push(Arg::target_var("includes", name));
+        args.push(Arg::target_var("defines", name));
+        args.extend(literals("", extra));
+        args
+    };
+    let std = |std: &Option<String>, var| std.as_ref().map(|_| Arg::var("-std=", var));
+
+    let mut nasm_flags = vec![Arg::var("-f ", "asformat"), Arg::target_var("includes", name), Arg::target_var("defines", name)];
+    nasm_flags.extend(literals("", &flags.asflags));
+    let compiles = [
+        (Language::C, Tool::Cc, "cflags", compile_flags(std(&project.c_std, "cstd"), &flags.cflags)),
+        (Language::Cxx, Tool::Cxx, "cxxflags", compile_flags(std(&project.cxx_std, "cxxstd"), &flags.cflags)),
+        (Language::Asm, Tool::Cc, "asflags", compile_flags(None, &flags.asflags)),
+        (Language::Nasm, Tool::As, "nasmflags", nasm_flags),
+    ]
+    .into_iter()
+    .filter(|(language, ..)| target.sources.iter().any(|s| s.language == *language))
+    .map(|(language, tool, var, flags)| Compile { language, tool, var, flags })
+    .collect();
+
+    let mut libs = Vec::new();
+    let mut rpath = None;
+    for dep in &target.link {
+        let dep_target = project.targets.iter().find(|t| &t.name == dep).unwrap();
+        libs.push(Arg::Built { prefix: "", path: dep_target.implib.clone().unwrap_or_else(|| dep_target.output.clone()) });
+        if dep_target.kind == TargetKind::Shared && !windows {
+            rpath = Some(if macos { "-Wl,-rpath,@loader_path" } else { "-Wl,-rpath,$ORIGIN" });
+        }
+    }
+    libs.extend(rpath.map(|r| Arg::Literal(r.to_string())));
+    libs.extend(flags.lib_dirs.iter().map(|dir| Arg::rooted("-L", dir)));
+    libs.extend(literals("", &flags.link_flags));
+    let libraries: Vec<_> = flags.libs.iter().map(|lib| Library::classify(lib)).collect();
+    libs.extend(libraries.iter().map(|lib| match lib {
+        Library::Flag(flag) => Arg::Literal(flag.clone()),
+        Library::File(path) => Arg::rooted("", path),
+        Library::Name(name) => Arg::Literal(format!("-l{name}")),
+    }));
+    for framework in &flags.frameworks {
+        libs.push(Arg::Literal("-framework".to_string()));
+        libs.push(Arg::Literal(framework.clone()));
+    }
+
+    let mut link_flags = match target.kind {
+        TargetKind::Shared if windows => vec![
+            Arg::Literal("-shared".to_string()),
+            Arg::Built { prefix: "-Wl,--out-implib,", path: target.implib.clone().unwrap_or_default() },
+        ],
+        TargetKind::Shared if macos => vec![
+            Arg::Literal("-dynamiclib".to_string()),
+            Arg::Literal("-install_name".to_string()),
+            Arg::Literal(format!("@rpath/{}", target.output)),
+        ],
+        TargetKind::Shared => vec![Arg::Literal("-shared".to_string()), Arg::Literal(format!("-Wl,-soname,{}", target.output))],
+        _ => Vec::new(),
+    };
+    link_flags.extend(march.iter().cloned());
+    link_flags.extend(sysroot.clone());
+    link_flags.extend(project.ld.as_ref().map(|ld| Arg::Literal(format!("-fuse-ld={ld}"))));
+    link_flags.extend(literals("", &flags.ldflags));
+
+    let link = match (target.kind, target.linker) {
+        (TargetKind::Static, _) => Link { tool: Tool::Ar, flags: Vec::new(), libs: Vec::new() },
+        (_, Language::Cxx) => Link { tool: Tool::Cxx, flags: link_flags, libs },
+        _ => Link { tool: Tool::Cc, flags: link_flags, libs },
+    };
+
+    Node {
+        target,
+        include_dirs,
+        libraries,
+        includes,
+        defines,
+        compiles,
+        link,
+        depends_on: project.targets.iter().filter(|t| target.depends_on.contains(&t.name)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Flags;
+    use crate::makefile::Make;
+    use crate::ninja::Ninja;
+    use crate::project::Source;
+    use std::collections::BTreeMap;
+
+    fn target(name: &str, kind: TargetKind, sources: &[(&str, &str, Language)], link: &[&str], flags: Flags) -> Target {
+        Target {
+            name: name.to_string(),
+            kind,
+            output: if kind == TargetKind::Static { format!("lib{name}.a") } else { name.to_string() },
+            implib: None,
+            sources: sources
+                .iter()
+                .map(|(path, object, language)| Source { path: path.to_string(), object: object.to_string(), language: *language })
+                .collect(),
+            linker: if sources.iter().any(|(.., l)| *l == Language::Cxx) { Language::Cxx } else { Language::C },
+            flags,
+            depends_on: link.iter().map(|l| l.to_string()).collect(),
+            link: link.iter().map(|l| l.to_string()).collect(),
+        }
+    }
+
+    fn fixture() -> Project {
+        let flags = Flags {
+            include_dirs: vec!["include".to_string()],
+            defines: vec![("DEBUG".to_string(), None), ("LEVEL".to_string(), Some("2".to_string()))],
+            cflags: vec!["-Wall".to_string()],
+            ldflags: vec!["-pthread".to_string()],
+            lib_dirs: vec!["vendor".to_string()],
+            libs: vec!["m".to_string(), "vendor/libz.a".to_string()],
+            ..Default::default()
+        };
+        Project {
+            language: "c".to_string(),
+            cc: "gcc".to_string(),
+            cxx: "g++".to_string(),
+            ar: "ar".to_string(),
+            ld: None,
+            versions: Vec::new(),
+            c_std: Some("c11".to_string()),
+            cxx_std: Some("c++17".to_string()),
+            assembler: None,
+            asm_format: "elf64",
+            platform: Platform::Linux,
+            architecture: "x64".to_string(),
+            march: "-m64".to_string(),
+            triple: None,
+            sysroot: None,
+            model: "debug".to_string(),
+            model_chain: vec!["debug".to_string()],
+            model_origins: Vec::new(),
+            optimization: vec!["-g".to_string(), "-O0".to_string()],
+            build_dir: "/work/build".to_string(),
+            root: "/work".to_string(),
+            targets: vec![
+                target("v", TargetKind::Static, &[("lib/v.c", "lib/v.o", Language::C)], &[], flags.clone()),
+                target(
+                    "app",
+                    TargetKind::Executable,
+                    &[("src/main.c", "src/main.o", Language::C), ("src/util.cpp", "src/util.o", Language::Cxx)],
+                    &["v"],
+                    flags,
+                ),
+            ],
+        }
+    }
+
+    // Top-level `NAME := value` / `name = value` lines, with references expanded.
+    fn variables(text: &str, separator: &str, reference: fn(&str) -> Option<(usize, usize, String)>) -> BTreeMap<String, String> {
+        let raw: BTreeMap<_, _> = text
+            .lines()
+            .filter(|line| !line.starts_with(char::is_whitespace))
+            .filter_map(|line| line.split_once(separator))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .collect();
+        let expand = |value: &str| {
+            let mut value = value.to_string();
+            while let Some((start, end, name)) = reference(&value) {
+                value.replace_range(start..end, raw.get(&name.to_lowercase()).map_or("", String::as_str));
+            }
+            value
+        };
+        raw.iter().map(|(name, value)| (name.clone(), expand(value))).collect()
+    }
+
+    fn make_reference(value: &str) -> Option<(usize, usize, String)> {
+        let start = value.find("$(")?;
+        let end = start + value[start..].find(')')? + 1;
+        Some((start, end, value[start + 2..end - 1].to_string()))
+    }
+
+    fn ninja_reference(value: &str) -> Option<(usize, usize, String)> {
+        let start = value.find('$')?;
+        if value[start + 1..].starts_with('{') {
+            let end = start + value[start..].find('}')? + 1;
+            return Some((start, end, value[start + 2..end - 1].to_string()));
+        }
+        let len = value[start + 1..].find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(value.len() - start - 1);
+        Some((start, start + 1 + len, value[start + 1..start + 1 + len].to_string()))
+    }
+
+    fn words(value: &str, build_dir: &str) -> Vec<String> {
+        value.split_whitespace().map(|w| w.strip_prefix(build_dir).unwrap_or(w).to_string()).collect()
+    }
+
+    #[test]
+    fn make_target_variables_do_not_collide() {
+        let mut project = fixture();
+        project.targets = ["x", "DIR_x", "OBJ"]
+            .map(|name| target(name, TargetKind::Static, &[("a.c", "a.o", Language::C)], &[], Flags::default()))
+            .into();
+        let text = Make.render(&build(&project));
+        let mut names: Vec<_> = text.lines().filter_map(|line| line.split_once(" := ")).map(|(name, _)| name).collect();
+        let count = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), count);
+    }
+
+    #[test]
+    fn ninja_regen_restats_unchanged_manifest() {
+        let project = fixture();
+        let text = Ninja { regenerate: "tr2make".to_string() }.render(&build(&project));
+        let rule: Vec<_> = text.lines().skip_while(|line| *line != "rule regen").take_while(|line| !line.is_empty()).collect();
+        assert!(rule.contains(&"  generator = 1"), "{rule:?}");
+        assert!(rule.contains(&"  restat = 1"), "{rule:?}");
+    }
+
+    #[test]
+    fn make_and_ninja_agree_on_the_graph() {
+        let project = fixture();
+        let graph = build(&project);
+        let make = variables(&Make.render(&graph), " := ", make_reference);
+        let ninja_text = Ninja { regenerate: "tr2make".to_string() }.render(&graph);
+        let ninja = variables(&ninja_text, " = ", ninja_reference);
+        let build_dir = "/work/build/";
+
+        for node in &graph.nodes {
+            let name = &node.target.name;
+            for compile in &node.compiles {
+                let var = format!("{}_{name}", compile.var);
+                assert_eq!(words(&make[&var], build_dir), words(&ninja[&var], build_dir), "{var}");
+            }
+            for var in [format!("ldflags_{name}"), format!("ldlibs_{name}")] {
+                assert_eq!(words(&make[&var], build_dir), words(&ninja[&var], build_dir), "{var}");
+            }
+
+            let inputs = ninja_text
+                .lines()
+                .find_map(|line| line.strip_prefix(&format!("build {}: ", node.target.output)))
+                .unwrap();
+            let objects: Vec<_> = inputs.split(" | ").next().unwrap().split_whitespace().skip(1).map(String::from).collect();
+            assert_eq!(words(&make[&format!("obj_{name}")], build_dir), objects, "{name} objects");
+        }
+
+        assert_eq!(words(&make["cflags_app"], build_dir).join(" "), "-g -O0 -std=c11 -m64 -I/work/include -DDEBUG -DLEVEL=2 -Wall");
+        assert_eq!(words(&make["ldlibs_app"], build_dir).join(" "), "libv.a -L/work/vendor -lm /work/vendor/libz.a");
+    }
+}
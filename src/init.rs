@@ -0,0 +1,196 @@
+use crate::error::{CONFIG_FILE, Error, Result};
+use crate::files;
+use crate::project::{self, Language};
+use std::collections::BTreeSet;
+use std::{env, fs, path::Path};
+
+pub struct Options {
+    pub language: Option<String>,
+    pub main: bool,
+    pub gitignore: bool,
+    pub force: bool,
+}
+
+const MAIN_C: &str = "#include <stdio.h>
+
+int main(void) {
+    printf(\"Hello, World!\\n\");
+    return 0;
+}
+";
+
+const MAIN_CPP: &str = "#include <iostream>
+
+int main() {
+    std::cout << \"Hello, World!\" << std::endl;
+    return 0;
+}
+";
+
+pub fn init(options: &Options) -> Result<()> {
+    if Path::new(CONFIG_FILE).exists() && !options.force {
+        return Err(Error::Usage {
+            message: format!("{CONFIG_FILE} already exists"),
+            help: Some("pass --force to overwrite it".to_string()),
+        });
+    }
+
+    let existing = files::resolve(&[".".to_string()], &["build".to_string()], &[])?;
+    let mut sources: Vec<_> = existing
+        .iter()
+        .filter(|f| matches!(Language::of(f), Some(Language::C | Language::Cxx)))
+        .cloned()
+        .collect();
+    let count = |language| sources.iter().filter(|f| Language::of(f) == Some(language)).count();
+    let (c_sources, cxx_sources) = (count(Language::C), count(Language::Cxx));
+
+    let language = match options.language.as_deref() {
+        Some(language @ ("c" | "c++")) => language,
+        Some(other) => {
+            return Err(project::suggest_language(
+                Error::Usage { message: format!("unsupported language `{other}`"), help: None },
+                other,
+            ));
+        }
+        None if c_sources > cxx_sources => "c",
+        None => "c++",
+    };
+    let (ext, standard) = if language == "c" { (".c", "11") } else { (".cpp", "17") };
+    let mixed = c_sources > 0 && cxx_sources > 0;
+
+    if options.main && sources.is_empty() {
+        let main = format!("main{ext}");
+        fs::write(&main, if language == "c" { MAIN_C } else { MAIN_CPP }).map_err(|e| Error::io(&main, e))?;
+        println!("Created {main}");
+        sources.push(main);
+    }
+
+    let mut patterns = BTreeSet::new();
+    for source in &sources {
+        let ext = source.rsplit_once('.').map_or("", |(_, ext)| ext);
+        match source.split_once('/') {
+            Some((dir, _)) => patterns.insert(format!("{dir}/**/*.{ext}")),
+            None => patterns.insert(format!("*.{ext}")),
+        };
+    }
+
+    let include_dirs: BTreeSet<_> = existing
+        .iter()
+        .filter(|f| [".h", ".hpp", ".hh", ".hxx"].iter().any(|e| f.ends_with(e)))
+        .filter_map(|f| f.rsplit_once('/').map(|(dir, _)| dir.to_string()))
+        .collect();
+
+    let content = render(language, standard, mixed, &patterns, &include_dirs);
+    fs::write(CONFIG_FILE, content).map_err(|e| Error::io(CONFIG_FILE, e))?;
+    if mixed {
+        println!("Created {CONFIG_FILE} ({c_sources} c and {cxx_sources} c++ sources found)");
+    } else {
+        println!("Created {CONFIG_FILE} ({} {language} sources found)", sources.len());
+    }
+    if sources.is_empty() {
+        println!("Add your sources to `files`, or rerun with --main --force for a skeleton");
+    }
+
+    if options.gitignore {
+        update_gitignore()?;
+    }
+    Ok(())
+}
+
+fn render(language: &str, standard: &str, mixed: bool, patterns: &BTreeSet<String>, include_dirs: &BTreeSet<String>) -> String {
+    let list = |items: &BTreeSet<String>| {
+        items.iter().map(|i| format!("  - \"{i}\"\n")).collect::<String>()
+    };
+    let other_standard = match (mixed, language) {
+        (true, "c") => "# Standard for the C++ sources\ncxx_standard: 17\n",
+        (true, _) => "# Standard for the C sources\nc_standard: 11\n",
+        _ => "# Standards for the other language in mixed C/C++ projects\n# c_standard: 11\n# cxx_standard: 17\n",
+    };
+    let files = if patterns.is_empty() {
+        let ext = if language == "c" { "c" } else { "cpp" };
+        format!(" []\n#   - \"src/**/*.{ext}\"\n")
+    } else {
+        format!("\n{}", list(patterns))
+    };
+    let include_dirs = if include_dirs.is_empty() {
+        "# include_dirs:\n#   - \"include\"\n".to_string()
+    } else {
+        format!("include_dirs:\n{}", list(include_dirs))
+    };
+
+    format!(r#"# tr2make project configuration
+# Run `tr2make schema` for the full list of keys.
+
+# Source language (c or c++) and the standard passed as -std=
+language: {language}
+standard: {standard}
+{other_standard}
+# Sources: file paths, directories or glob patterns
+files:{files}# exclude:
+#   - "src/legacy"
+
+# Name of the produced binary and its type (executable, static or shared)
+target: "{target}"
+type: executable
+architecture: {architecture}
+# Platform the build runs on (defaults to the current one):
+# linux, windows-mingw, windows-cmd, macos or freebsd
+# platform: linux
+
+# Compiler toolchain: gcc, clang, a versioned name like gcc-13,
+# or a map of cc/cxx/ar/ld. CC, CXX and AR from the environment win,
+# except with `cross:`, which always picks the prefixed tools.
+# toolchain: gcc
+
+# Cross-compilation: tools become <triple>-gcc, <triple>-ar, ...
+# cross:
+#   triple: aarch64-linux-gnu
+#   sysroot: "/opt/sysroots/aarch64"
+
+{include_dirs}# defines:
+#   - "VERSION=1"
+# libs:
+#   - "pthread"
+
+# Build models, selected with `tr2make <model>`
+model:
+  debug: {{}}
+  release: {{}}
+"#,
+        target = target_name(),
+        architecture = project::host_architecture(),
+    )
+}
+
+fn target_name() -> String {
+    let dir = env::current_dir().ok();
+    let name = dir
+        .as_deref()
+        .and_then(Path::file_name)
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    match project::check_target_name(&name, &["target"]) {
+        Ok(()) => name,
+        Err(_) => "app".to_string(),
+    }
+}
+
+fn update_gitignore() -> Result<()> {
+    let existing = fs::read_to_string(".gitignore").unwrap_or_default();
+    if existing.lines().any(|l| matches!(l.trim(), "build" | "build/" | "/build" | "/build/")) {
+        return Ok(());
+    }
+
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str("/build/\n");
+    fs::write(".gitignore", content).map_err(|e| Error::io(".gitignore", e))?;
+    println!("Added /build/ to .gitignore");
+    Ok(())
+}
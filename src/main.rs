@@ -1,28 +1,21 @@
-use serde::Deserialize;
-use serde_yaml::Number;
-use std::{fs, path::Path};
-use clap::{Parser, ValueEnum};
-
-#[derive(Deserialize)]
-struct Config {
-    language: String,
-    standard: Number,
-    files: Vec<String>,
-    target: String,
-    architecture: String,
-    model: BuildModels,
-}
-
-#[derive(Deserialize)]
-struct BuildModels {
-    debug: BuildConfig,
-    release: BuildConfig,
-}
-
-#[derive(Deserialize)]
-struct BuildConfig {
-    targetdir: String,
-}
+use std::{fs, io, process};
+use clap::{Parser, Subcommand};
+use config::{Backend, Config, Platform};
+use error::{CONFIG_FILE, Error, Result};
+use schema::Schema;
+
+mod cmake;
+mod config;
+mod doctor;
+mod driver;
+mod error;
+mod files;
+mod graph;
+mod init;
+mod makefile;
+mod ninja;
+mod project;
+mod schema;
 
 #[derive(Parser)]
 #[command(version, about = r#"
@@ -31,121 +24,167 @@ struct BuildConfig {
   | | | '__| __) || |\/| | / _` || |/ // _ \
   | | | |   / __/ | |  | || (_| ||   <|  __/
   |_| |_|  |_____||_|  |_| \__,_||_|\_\\___|"#, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
-    #[arg(index = 1, short, long, default_value = "debug")]
-    model: BuildModel,
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[arg(default_value = "debug")]
+    model: String,
+    /// Compiler toolchain overriding .tr2make and CC/CXX/AR (gcc, clang, gcc-13, ...)
+    #[arg(long, global = true)]
+    toolchain: Option<String>,
+    /// Platform the build runs on (linux, windows-mingw, windows-cmd, macos, freebsd)
+    #[arg(long, global = true)]
+    platform: Option<String>,
+    /// Build file to generate: make (Makefile) or ninja (build.ninja)
+    #[arg(long, global = true, default_value = "make")]
+    backend: String,
+    /// Probe the compiler and its flags before writing the Makefile
+    #[arg(long, global = true)]
+    check_toolchain: bool,
 }
 
-#[derive(Clone, ValueEnum)]
-enum BuildModel {
-    Debug,
-    Release,
+#[derive(Subcommand)]
+enum Command {
+    /// Validate .tr2make for every build model without writing any Makefile
+    Check,
+    /// Generate the Makefile if needed and run make
+    Build {
+        #[arg(default_value = "debug")]
+        model: String,
+        /// Number of parallel make jobs (defaults to the number of CPUs)
+        #[arg(short, long)]
+        jobs: Option<usize>,
+    },
+    /// Build, then run an executable target with the given arguments
+    Run {
+        #[arg(default_value = "debug")]
+        model: String,
+        /// Number of parallel make jobs (defaults to the number of CPUs)
+        #[arg(short, long)]
+        jobs: Option<usize>,
+        /// Executable target to run when the project has several
+        #[arg(long)]
+        target: Option<String>,
+        /// Arguments forwarded to the program
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Remove the objects and binaries of a build model
+    Clean {
+        #[arg(default_value = "debug")]
+        model: String,
+    },
+    /// Check that the configured compilers exist and accept the requested flags
+    Doctor {
+        #[arg(default_value = "debug")]
+        model: String,
+    },
+    /// Write a CMakeLists.txt equivalent to .tr2make, with build models as configurations
+    Export {
+        /// Build system to export to (cmake)
+        format: String,
+        /// Overwrite a CMakeLists.txt that was not generated by tr2make
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print a JSON Schema describing .tr2make
+    Schema,
+    /// Write a starter .tr2make for the sources in the current directory
+    Init {
+        /// Language to use instead of guessing from the sources (c or c++)
+        #[arg(long)]
+        language: Option<String>,
+        /// Create a main.c/main.cpp skeleton when no sources exist
+        #[arg(long)]
+        main: bool,
+        /// Add the build directory to .gitignore
+        #[arg(long)]
+        gitignore: bool,
+        /// Overwrite an existing .tr2make
+        #[arg(long)]
+        force: bool,
+    },
 }
 
-impl BuildModel {
-    fn as_str(&self) -> &'static str {
-        match self {
-            BuildModel::Debug => "debug",
-            BuildModel::Release => "release",
-        }
-    }
-}
-
-fn main() {
-    let config: Config = serde_yaml::from_reader(fs::File::open(".tr2make").unwrap()).unwrap();
-    let args = Args::parse();
-
-    let build_dir = format!("build/{}-{}", args.model.as_str(), config.architecture);
-    fs::create_dir_all(&build_dir).unwrap();
-
-    let (file_ext, compiler, std_prefix) = match config.language.as_str() {
-        "c" => (".c", "gcc", "c"),
-        "c++" => (".cpp", "g++", "c++"),
-        _ => panic!("Unsupported language"),
+fn load_config(args: &Args, source: &mut Option<String>) -> Result<Config> {
+    let text = match fs::read_to_string(CONFIG_FILE) {
+        Ok(text) => source.insert(text),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(Error::MissingConfig),
+        Err(e) => return Err(Error::io(CONFIG_FILE, e)),
     };
+    let mut config: Config = serde_yaml::from_str(text).map_err(Error::Syntax)?;
 
-    let files: Vec<_> = config.files
-        .iter()
-        .filter(|f| f.ends_with(file_ext))
-        .cloned()
-        .collect();
-
-    if files.is_empty() {
-        panic!("No valid {} files found", file_ext);
+    if let Some(toolchain) = &args.toolchain {
+        if project::named_toolchain(toolchain).is_none() {
+            return Err(Error::Usage { message: format!("unknown toolchain `{toolchain}`"), help: None }
+                .suggest(toolchain, ["gcc", "clang"]));
+        }
+        config.toolchain_override = Some(toolchain.clone());
     }
-
-    let (target, clean_cmd, mkdir_cmd) = if cfg!(target_os = "windows") {
-        (
-            format!("{}.exe", config.target),
-            "del /Q $(OBJ) $(TARGET)",
-            "@if not exist \"$(OBJ_DIR)\" mkdir \"$(OBJ_DIR)\""
-        )
-    } else {
-        (
-            config.target.clone(),
-            "rm -f $(OBJ) $(TARGET)",
-            "@mkdir -p $(OBJ_DIR)"
-        )
-    };
-
-    let march = match config.architecture.as_str() {
-        "x64" => "-m64",
-        "x86" => "-m32",
-        arch => &format!("-m{arch}")[..],
+    if let Some(platform) = &args.platform {
+        config.platform_override = Some(Platform::from_name(platform).ok_or_else(|| {
+            Error::Usage { message: format!("unknown platform `{platform}`"), help: None }
+                .suggest(platform, Platform::ALL.iter().map(|(name, _)| *name))
+        })?);
+    }
+    config.backend = match args.backend.as_str() {
+        "make" => Backend::Make,
+        "ninja" => Backend::Ninja,
+        other => {
+            return Err(Error::Usage { message: format!("unknown backend `{other}`"), help: None }
+                .suggest(other, ["make", "ninja"]));
+        }
     };
+    config.check_toolchain = args.check_toolchain;
+    Ok(config)
+}
 
-    let debug_flag = if matches!(args.model, BuildModel::Debug) { "1" } else { "0" };
-    let optimization = if debug_flag == "1" { "-g -O0 -DDEBUG" } else { "-O2" };
-
-    let makefile_content = format!(r#"# {lang} Project Makefile
-CC := {compiler}
-SRC := {files}
-TARGET := {target_dir}/{target}
-STD := {std_prefix}{standard}
-ARCH := {arch}
-DEBUG := {debug}
-
-OBJ_DIR := {target_dir}/obj
-OBJ := $(addprefix $(OBJ_DIR)/, $(SRC:{file_ext}=.o))
-
-CFLAGS := {optimization} -std=$(STD) {march}
-LDFLAGS := {march}
-
-all: create_dirs $(TARGET)
-
-$(TARGET): $(OBJ)
-	$(CC) $^ -o $@ $(LDFLAGS)
-
-$(OBJ_DIR)/%.o: %{file_ext}
-	{mkdir_cmd}
-	$(CC) $(CFLAGS) -c $< -o $@
-
-create_dirs:
-	{mkdir_cmd}
-
-clean:
-	{clean_cmd}
+fn check(config: &Config) -> Result<()> {
+    for model in config.model_names() {
+        project::resolve(config, model)?;
+    }
+    println!("{CONFIG_FILE} is valid ({} build models checked)", config.model_names().len());
+    Ok(())
+}
 
-.PHONY: all clean create_dirs
-"#,
-        lang = config.language.to_uppercase(),
-        compiler = compiler,
-        files = files.join(" "),
-        target_dir = build_dir,
-        target = target,
-        std_prefix = std_prefix,
-        standard = config.standard,
-        arch = config.architecture,
-        debug = debug_flag,
-        file_ext = file_ext,
-        optimization = optimization,
-        march = march,
-        mkdir_cmd = mkdir_cmd,
-        clean_cmd = clean_cmd
-    );
+fn run(args: &Args, source: &mut Option<String>) -> Result<()> {
+    match &args.command {
+        Some(Command::Check) => check(&load_config(args, source)?),
+        Some(Command::Schema) => {
+            println!("{}", Config::schema());
+            Ok(())
+        }
+        Some(Command::Init { language, main, gitignore, force }) => init::init(&init::Options {
+            language: language.clone(),
+            main: *main,
+            gitignore: *gitignore,
+            force: *force,
+        }),
+        Some(Command::Build { model, jobs }) => driver::build(&load_config(args, source)?, model, *jobs).map(|_| ()),
+        Some(Command::Run { model, jobs, target, args: program_args }) => {
+            driver::run(&load_config(args, source)?, model, *jobs, target.as_deref(), program_args)
+        }
+        Some(Command::Doctor { model }) => {
+            let config = load_config(args, source)?;
+            doctor::doctor(&config, model, source.as_deref())
+        }
+        Some(Command::Export { format, force }) => match format.as_str() {
+            "cmake" => cmake::export(&load_config(args, source)?, *force),
+            other => Err(Error::Usage { message: format!("unknown export format `{other}`"), help: None }
+                .suggest(other, ["cmake"])),
+        },
+        Some(Command::Clean { model }) => driver::clean(&load_config(args, source)?, model),
+        None => driver::generate(&load_config(args, source)?, &args.model).map(|_| ()),
+    }
+}
 
-    let makefile_path = Path::new(&build_dir).join("Makefile");
-    fs::write(&makefile_path, makefile_content).unwrap();
+fn main() {
+    let args = Args::parse();
 
-    println!("Makefile generated at: {}", makefile_path.display());
-}
\ No newline at end of file
+    let mut source = None;
+    if let Err(err) = run(&args, &mut source) {
+        eprintln!("{}", err.render(source.as_deref()));
+        process::exit(err.exit_code());
+    }
+}
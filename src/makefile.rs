@@ -0,0 +1,196 @@
+use crate::config::Platform;
+use crate::graph::{Arg, Generator, Graph, Node, Tool, shell_quote};
+use crate::project::Language;
+use std::path::Path;
+
+pub struct Make;
+
+fn make_arg(platform: Platform, arg: &str) -> String {
+    shell_quote(platform, arg).replace('$', "$$").replace('#', "\\#")
+}
+
+fn render_arg(platform: Platform, arg: &Arg) -> String {
+    match arg {
+        Arg::Literal(value) => make_arg(platform, value),
+        Arg::Raw(command) => command.replace('$', "$$").replace('#', "\\#"),
+        Arg::Rooted { prefix, path } if Path::new(path).is_absolute() => make_arg(platform, &format!("{prefix}{path}")),
+        Arg::Rooted { prefix, path } => format!("{prefix}$(ROOT)/{}", make_arg(platform, path)),
+        Arg::Built { prefix, path } => format!("{prefix}$(BUILD_DIR)/{}", make_arg(platform, path)),
+        Arg::Var { prefix, name, target: None } => format!("{prefix}$({})", name.to_uppercase()),
+        Arg::Var { prefix, name, target: Some(target) } => format!("{prefix}$({}_{target})", name.to_uppercase()),
+    }
+}
+
+fn render_args(platform: Platform, args: &[Arg]) -> String {
+    args.iter().map(|arg| render_arg(platform, arg)).collect::<Vec<_>>().join(" ")
+}
+
+fn rooted(path: &str) -> String {
+    if Path::new(path).is_absolute() {
+        path.to_string()
+    } else {
+        format!("$(ROOT)/{path}")
+    }
+}
+
+fn group(language: Language) -> &'static str {
+    match language {
+        Language::C => "COBJ",
+        Language::Cxx => "CXXOBJ",
+        Language::Asm => "ASOBJ",
+        Language::Nasm => "NASMOBJ",
+    }
+}
+
+fn object_list(node: &Node, include: impl Fn(Language) -> bool) -> String {
+    node.target.sources
+        .iter()
+        .filter(|s| include(s.language))
+        .map(|s| format!("$(OBJDIR_{})/{}", node.target.name, s.object))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Generator for Make {
+    fn file_name(&self) -> &'static str {
+        "Makefile"
+    }
+
+    fn render(&self, graph: &Graph) -> String {
+        let project = graph.project;
+        let platform = project.platform;
+        let mkdir_cmd = |dir: &str| if platform == Platform::WindowsCmd {
+            format!("@if not exist \"{dir}\" mkdir \"$(subst /,\\,{dir})\"")
+        } else {
+            format!("@mkdir -p {dir}")
+        };
+        let names = graph.nodes.iter().map(|n| n.target.name.as_str()).collect::<Vec<_>>().join(" ");
+
+        let mut out = format!(r#"# {lang} Project Makefile
+{comments}MAKEFILE_DIR := $(patsubst %/,%,$(dir $(abspath $(lastword $(MAKEFILE_LIST)))))
+ROOT := {root}
+BUILD_DIR := {build_dir}
+
+{variables}DEPFLAGS := -MMD -MP
+
+all: create_dirs {names}
+"#,
+            lang = project.language.to_uppercase(),
+            comments = graph.comments.iter().map(|c| format!("# {c}\n")).collect::<String>(),
+            root = if Path::new(&project.root).is_absolute() {
+                project.root.clone()
+            } else {
+                format!("$(abspath $(MAKEFILE_DIR)/{})", project.root)
+            },
+            build_dir = rooted(&project.build_dir),
+            variables = graph.variables
+                .iter()
+                .map(|(name, value)| format!("{} := {}\n", name.to_uppercase(), render_arg(platform, value)))
+                .collect::<String>(),
+        );
+
+        for node in &graph.nodes {
+            out.push_str(&render_node(graph, node, &mkdir_cmd));
+        }
+
+        let mut clean = Vec::new();
+        for node in &graph.nodes {
+            let name = &node.target.name;
+            clean.push(format!("$(OBJ_{name}) $(DEP_{name}) $(TARGET_{name})"));
+            if node.target.implib.is_some() {
+                clean.push(format!("$(IMPLIB_{name})"));
+            }
+        }
+
+        out.push_str(&format!(r#"
+create_dirs:
+	{mkdir}
+
+clean:
+	{clean_cmd}
+
+.PHONY: all clean create_dirs {names}
+"#,
+            mkdir = mkdir_cmd("$(BUILD_DIR)"),
+            clean_cmd = if platform == Platform::WindowsCmd {
+                format!("del /Q $(subst /,\\,{})", clean.join(" "))
+            } else {
+                format!("rm -f {}", clean.join(" "))
+            },
+        ));
+        out
+    }
+}
+
+fn render_node(graph: &Graph, node: &Node, mkdir_cmd: &dyn Fn(&str) -> String) -> String {
+    let target = node.target;
+    let name = &target.name;
+    let platform = graph.project.platform;
+    let nasm_depflags = if graph.project.assembler.as_deref() == Some("nasm") { " -MD $(@:.o=.d)" } else { "" };
+
+    let link_cmd = match node.link.tool {
+        Tool::Ar => format!("$(AR) rcs $@ $(OBJ_{name})"),
+        tool => format!("$({}) $(OBJ_{name}) -o $@ $(LDFLAGS_{name}) $(LDLIBS_{name})", tool.var().to_uppercase()),
+    };
+    let implib = match &target.implib {
+        Some(implib) => format!("IMPLIB_{name} := $(BUILD_DIR)/{implib}\n"),
+        None => String::new(),
+    };
+    let prerequisites: Vec<_> = std::iter::once(format!("$(OBJ_{name})"))
+        .chain(node.depends_on.iter().map(|dep| format!("$(TARGET_{})", dep.name)))
+        .collect();
+
+    format!(r#"
+# {name}
+SRC_{name} := {files}
+TARGET_{name} := $(BUILD_DIR)/{output}
+{implib}OBJDIR_{name} := $(BUILD_DIR)/obj/{name}
+{object_groups}OBJ_{name} := {objects}
+DEP_{name} := $(OBJ_{name}:.o=.d)
+
+INCLUDES_{name} := {includes}
+DEFINES_{name} := {defines}
+
+{compile_flags}LDFLAGS_{name} := {ldflags}
+LDLIBS_{name} := {ldlibs}
+
+{name}: $(TARGET_{name})
+
+$(TARGET_{name}): {prerequisites}
+	{link_cmd}
+
+{compile_rules}{object_rules}
+-include $(DEP_{name})
+"#,
+        files = target.sources.iter().map(|s| s.path.as_str()).collect::<Vec<_>>().join(" "),
+        output = target.output,
+        objects = object_list(node, |_| true),
+        object_groups = node.compiles
+            .iter()
+            .map(|c| format!("{}_{name} := {}\n", group(c.language), object_list(node, |l| l == c.language)))
+            .collect::<String>(),
+        includes = render_args(platform, &node.includes),
+        defines = render_args(platform, &node.defines),
+        compile_flags = node.compiles
+            .iter()
+            .map(|c| format!("{}_{name} := {}\n", c.var.to_uppercase(), render_args(platform, &c.flags)))
+            .collect::<String>(),
+        ldflags = render_args(platform, &node.link.flags),
+        ldlibs = render_args(platform, &node.link.libs),
+        prerequisites = prerequisites.join(" "),
+        compile_rules = node.compiles
+            .iter()
+            .map(|c| {
+                let command = match c.tool {
+                    Tool::As => format!("$(AS) $(NASMFLAGS_{name}){nasm_depflags} $< -o $@"),
+                    tool => format!("$({}) $({}_{name}) $(DEPFLAGS) -c $< -o $@", tool.var().to_uppercase(), c.var.to_uppercase()),
+                };
+                format!("$({}_{name}):\n\t{}\n\t{command}\n\n", group(c.language), mkdir_cmd("$(@D)"))
+            })
+            .collect::<String>(),
+        object_rules = target.sources
+            .iter()
+            .map(|s| format!("$(OBJDIR_{name})/{}: {}\n", s.object, rooted(&s.path)))
+            .collect::<String>(),
+    )
+}
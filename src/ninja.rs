@@ -0,0 +1,196 @@
+use crate::config::Platform;
+use crate::error::CONFIG_FILE;
+use crate::graph::{Arg, Generator, Graph, Node, Tool, shell_quote};
+use crate::project::Language;
+use std::path::Path;
+
+pub struct Ninja {
+    pub regenerate: String,
+}
+
+fn escape_path(path: &str) -> String {
+    path.replace('$', "$$").replace(' ', "$ ").replace(':', "$:")
+}
+
+fn ninja_arg(platform: Platform, arg: &str) -> String {
+    shell_quote(platform, arg).replace('$', "$$")
+}
+
+fn render_arg(platform: Platform, arg: &Arg) -> String {
+    match arg {
+        Arg::Literal(value) => ninja_arg(platform, value),
+        Arg::Raw(command) => command.replace('$', "$$"),
+        Arg::Rooted { prefix, path } if Path::new(path).is_absolute() => ninja_arg(platform, &format!("{prefix}{path}")),
+        Arg::Rooted { prefix, path } => format!("{prefix}$root/{}", ninja_arg(platform, path)),
+        Arg::Built { prefix, path } => ninja_arg(platform, &format!("{prefix}{path}")),
+        Arg::Var { prefix, name, target: None } => format!("{prefix}${{{name}}}"),
+        Arg::Var { prefix, name, target: Some(target) } => format!("{prefix}${{{name}_{target}}}"),
+    }
+}
+
+fn render_args(platform: Platform, args: &[Arg]) -> String {
+    args.iter().map(|arg| render_arg(platform, arg)).collect::<Vec<_>>().join(" ")
+}
+
+fn rooted(path: &str) -> String {
+    if Path::new(path).is_absolute() {
+        escape_path(path)
+    } else {
+        format!("$root/{}", escape_path(path))
+    }
+}
+
+fn rule(language: Language) -> &'static str {
+    match language {
+        Language::C => "cc",
+        Language::Cxx => "cxx",
+        Language::Asm => "as",
+        Language::Nasm => "nasm",
+    }
+}
+
+impl Generator for Ninja {
+    fn file_name(&self) -> &'static str {
+        "build.ninja"
+    }
+
+    fn render(&self, graph: &Graph) -> String {
+        let project = graph.project;
+        let platform = project.platform;
+        let regen = match platform {
+            Platform::WindowsCmd => format!("cmd /c \"cd /d {} && {}\"", shell_quote(platform, &project.root), self.regenerate),
+            _ => format!("cd {} && {}", shell_quote(platform, &project.root), self.regenerate),
+        };
+        let nasm = project.assembler.as_deref() == Some("nasm");
+
+        let mut out = format!(r#"# {lang} Project build.ninja
+{comments}ninja_required_version = 1.7
+
+root = {root}
+{variables}
+rule cc
+  command = $cc $flags -MMD -MF $out.d -c $in -o $out
+  depfile = $out.d
+  deps = gcc
+  description = CC $out
+
+rule cxx
+  command = $cxx $flags -MMD -MF $out.d -c $in -o $out
+  depfile = $out.d
+  deps = gcc
+  description = CXX $out
+
+rule as
+  command = $cc $flags -MMD -MF $out.d -c $in -o $out
+  depfile = $out.d
+  deps = gcc
+  description = AS $out
+
+rule nasm
+  command = $as $flags{nasm_depflags} $in -o $out{nasm_depfile}
+  description = NASM $out
+
+rule ar
+  command = $ar rcs $out $in
+  description = AR $out
+
+rule link
+  command = $ld $in -o $out $ldflags $ldlibs
+  description = LINK $out
+
+rule regen
+  command = {regen}
+  description = Regenerating build.ninja
+  generator = 1
+  restat = 1
+
+rule clean
+  command = ninja -t clean
+  description = Cleaning
+
+build build.ninja: regen {config}
+"#,
+            lang = project.language.to_uppercase(),
+            comments = graph.comments.iter().map(|c| format!("# {c}\n")).collect::<String>(),
+            root = escape_path(&project.root),
+            variables = graph.variables
+                .iter()
+                .map(|(name, value)| format!("{name} = {}\n", render_arg(platform, value)))
+                .collect::<String>(),
+            nasm_depflags = if nasm { " -MD $out.d" } else { "" },
+            nasm_depfile = if nasm { "\n  depfile = $out.d\n  deps = gcc" } else { "" },
+            regen = regen.replace('$', "$$"),
+            config = rooted(CONFIG_FILE),
+        );
+
+        for node in &graph.nodes {
+            out.push_str(&render_node(graph, node));
+        }
+
+        out.push_str(&format!(r#"
+build all: phony {all}
+build create_dirs: phony
+build clean: clean
+
+default all
+"#,
+            all = graph.nodes.iter().map(|n| escape_path(&n.target.output)).collect::<Vec<_>>().join(" "),
+        ));
+        out
+    }
+}
+
+fn render_node(graph: &Graph, node: &Node) -> String {
+    let target = node.target;
+    let name = &target.name;
+    let platform = graph.project.platform;
+
+    let mut out = format!(
+        "\n# {name}\nincludes_{name} = {}\ndefines_{name} = {}\n",
+        render_args(platform, &node.includes),
+        render_args(platform, &node.defines),
+    );
+    for compile in &node.compiles {
+        out.push_str(&format!("{}_{name} = {}\n", compile.var, render_args(platform, &compile.flags)));
+    }
+    out.push_str(&format!(
+        "ldflags_{name} = {}\nldlibs_{name} = {}\n\n",
+        render_args(platform, &node.link.flags),
+        render_args(platform, &node.link.libs),
+    ));
+
+    let mut objects = Vec::new();
+    for source in &target.sources {
+        let compile = node.compiles.iter().find(|c| c.language == source.language).unwrap();
+        let object = escape_path(&format!("obj/{name}/{}", source.object));
+        out.push_str(&format!(
+            "build {object}: {} {}\n  flags = ${}_{name}\n",
+            rule(source.language),
+            rooted(&source.path),
+            compile.var,
+        ));
+        objects.push(object);
+    }
+
+    let output = escape_path(&target.output);
+    let implib = match &target.implib {
+        Some(implib) => format!(" | {}", escape_path(implib)),
+        None => String::new(),
+    };
+    let implicit = match node.depends_on.is_empty() {
+        true => String::new(),
+        false => format!(" | {}", node.depends_on.iter().map(|t| escape_path(&t.output)).collect::<Vec<_>>().join(" ")),
+    };
+    match node.link.tool {
+        Tool::Ar => out.push_str(&format!("build {output}: ar {}{implicit}\n", objects.join(" "))),
+        tool => out.push_str(&format!(
+            "build {output}{implib}: link {}{implicit}\n  ld = ${}\n  ldflags = $ldflags_{name}\n  ldlibs = $ldlibs_{name}\n",
+            objects.join(" "),
+            tool.var(),
+        )),
+    }
+    if target.output != *name {
+        out.push_str(&format!("build {name}: phony {output}\n"));
+    }
+    out
+}
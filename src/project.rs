@@ -0,0 +1,687 @@
+use crate::config::{BuildConfig, Config, Flags, Platform, TargetConfig, TargetKind, Toolchain};
+use crate::error::{Error, Result};
+use crate::files;
+use std::collections::{BTreeMap, BTreeSet};
+use serde_yaml::Number;
+use std::{env, path::Path};
+
+pub struct Project {
+    pub language: String,
+    pub cc: String,
+    pub cxx: String,
+    pub ar: String,
+    pub ld: Option<String>,
+    pub versions: Vec<(String, String)>,
+    pub c_std: Option<String>,
+    pub cxx_std: Option<String>,
+    pub assembler: Option<String>,
+    pub asm_format: &'static str,
+    pub platform: Platform,
+    pub architecture: String,
+    pub march: String,
+    pub triple: Option<String>,
+    pub sysroot: Option<String>,
+    pub model: String,
+    pub model_chain: Vec<String>,
+    pub model_origins: Vec<(&'static str, String, String)>,
+    pub optimization: Vec<String>,
+    pub build_dir: String,
+    pub root: String,
+    pub targets: Vec<Target>,
+}
+
+pub struct Target {
+    pub name: String,
+    pub kind: TargetKind,
+    pub output: String,
+    pub implib: Option<String>,
+    pub sources: Vec<Source>,
+    pub linker: Language,
+    pub flags: Flags,
+    pub depends_on: Vec<String>,
+    pub link: Vec<String>,
+}
+
+const RESERVED_TARGETS: &[&str] = &["all", "clean", "create_dirs"];
+pub const C_STANDARDS: &[&str] = &["89", "90", "99", "11", "17", "18", "23"];
+pub const CXX_STANDARDS: &[&str] = &["98", "03", "11", "14", "17", "20", "23", "26"];
+pub const ARCHITECTURES: &[(&str, &str, &str)] = &[
+    ("x64", "-m64", "x86_64-linux-gnu"),
+    ("x86", "-m32", "i686-linux-gnu"),
+    ("arm64", "", "aarch64-linux-gnu"),
+    ("armv7", "-march=armv7-a -mfpu=neon -mfloat-abi=hard", "arm-linux-gnueabihf"),
+    ("armv6", "-march=armv6 -mfpu=vfp -mfloat-abi=hard", "arm-linux-gnueabihf"),
+    ("riscv64", "-march=rv64gc -mabi=lp64d", "riscv64-linux-gnu"),
+    ("ppc64le", "-mcpu=power8", "powerpc64le-linux-gnu"),
+    ("s390x", "", "s390x-linux-gnu"),
+];
+const LANGUAGE_ALIASES: &[(&str, &str)] = &[("c", "c"), ("c++", "c++"), ("cpp", "c++"), ("cxx", "c++"), ("cc", "c++"), ("cplusplus", "c++")];
+pub const ARCHITECTURE_ALIASES: &[(&str, &str)] = &[
+    ("x86_64", "x64"),
+    ("amd64", "x64"),
+    ("i386", "x86"),
+    ("i686", "x86"),
+    ("aarch64", "arm64"),
+    ("armhf", "armv7"),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Language {
+    C,
+    Cxx,
+    Asm,
+    Nasm,
+}
+
+impl Language {
+    pub fn of(path: &str) -> Option<Language> {
+        if path.ends_with(".c") {
+            Some(Language::C)
+        } else if [".cpp", ".cc", ".cxx", ".c++"].iter().any(|ext| path.ends_with(ext)) {
+            Some(Language::Cxx)
+        } else if path.ends_with(".s") || path.ends_with(".S") {
+            Some(Language::Asm)
+        } else if path.ends_with(".asm") || path.ends_with(".nasm") {
+            Some(Language::Nasm)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct Source {
+    pub path: String,
+    pub object: String,
+    pub language: Language,
+}
+
+pub fn normalize_dir(dir: &str) -> String {
+    let dir = dir.replace('\\', "/");
+    match dir.trim_end_matches('/') {
+        "" => ".".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+fn model_chain(config: &Config, name: &str, visiting: &mut Vec<String>) -> Result<Vec<(String, BuildConfig)>> {
+    if let Some(start) = visiting.iter().position(|n| n == name) {
+        let mut cycle = visiting[start..].to_vec();
+        cycle.push(name.to_string());
+        return Err(Error::config(
+            format!("build model inheritance cycle: {}", cycle.join(" -> ")),
+            &["model", &visiting[visiting.len() - 1], "inherits"],
+        ));
+    }
+
+    let builtin = BuildConfig::builtin(name);
+    let declared = config.model.get(name);
+    if builtin.is_none() && declared.is_none() {
+        let err = match visiting.last() {
+            Some(child) => Error::config(
+                format!("build model `{child}` inherits unknown model `{name}`"),
+                &["model", child, "inherits"],
+            ),
+            None => Error::Usage {
+                message: format!("unknown build model `{name}`"),
+                help: Some(format!("available models: {}", config.model_names().join(", "))),
+            },
+        };
+        return Err(err.suggest(name, config.model_names()));
+    }
+
+    visiting.push(name.to_string());
+    let mut chain = match declared.and_then(|m| m.inherits.as_deref()) {
+        Some(parent) => model_chain(config, parent, visiting)?,
+        None => Vec::new(),
+    };
+    visiting.pop();
+
+    chain.extend(builtin.map(|m| (name.to_string(), m)));
+    chain.extend(declared.map(|m| (name.to_string(), m.clone())));
+    Ok(chain)
+}
+
+fn root_from(build_dir: &str) -> Result<String> {
+    let path = Path::new(build_dir);
+    if path.is_absolute() || build_dir.split('/').any(|c| c == "..") {
+        let cwd = env::current_dir().map_err(|e| Error::io(".", e))?;
+        return Ok(normalize_dir(&cwd.to_string_lossy()));
+    }
+
+    let depth = build_dir.split('/').filter(|c| !c.is_empty() && *c != ".").count();
+    Ok(match depth {
+        0 => ".".to_string(),
+        _ => vec![".."; depth].join("/"),
+    })
+}
+
+pub fn resolve(config: &Config, model_name: &str) -> Result<Project> {
+    let chain = model_chain(config, model_name, &mut Vec::new())?;
+
+    let mut flags = config.flags();
+    let mut optimization = Vec::new();
+    let mut targetdir = None;
+    let mut origins: Vec<(&'static str, String, String)> = Vec::new();
+    for (name, model) in &chain {
+        if let Some(opt) = &model.optimization {
+            optimization = opt.clone();
+            origins.retain(|(key, _, _)| *key != "optimization");
+            origins.push(("optimization", opt.join(" "), name.clone()));
+        }
+        // Each model gets its own output directory, so targetdir is never inherited.
+        if name == model_name && model.targetdir.is_some() {
+            targetdir = model.targetdir.as_ref();
+        }
+
+        let model_flags = model.flags();
+        for (key, value) in model_flags.describe() {
+            if key == "defines" {
+                let define = value.split('=').next().unwrap_or_default();
+                origins.retain(|(k, v, _)| *k != "defines" || v.split('=').next() != Some(define));
+            }
+            origins.push((key, value, name.clone()));
+        }
+        flags.extend(model_flags);
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    for (name, _) in chain.iter().rev() {
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+
+    let build_dir = match targetdir {
+        Some(dir) => normalize_dir(dir),
+        None => format!("build/{}-{}", model_name, config.architecture),
+    };
+
+    let primary = (&config.standard, "standard");
+    let c_standard = config.c_standard.as_ref().map(|s| (s, "c_standard"));
+    let cxx_standard = config.cxx_standard.as_ref().map(|s| (s, "cxx_standard"));
+    let (c_standard, cxx_standard) = match config.language.as_str() {
+        "c" => (c_standard.or(Some(primary)), cxx_standard),
+        "c++" => (c_standard, cxx_standard.or(Some(primary))),
+        other => {
+            return Err(suggest_language(Error::config(format!("unsupported language `{other}`"), &["language"]), other));
+        }
+    };
+    let c_std = c_standard.map(|(s, key)| standard("c", s, C_STANDARDS, key)).transpose()?;
+    let cxx_std = cxx_standard.map(|(s, key)| standard("c++", s, CXX_STANDARDS, key)).transpose()?;
+
+    let platform = config.platform_override.or(config.platform).unwrap_or_else(Platform::host);
+    let (arch, march, arch_triple) = architecture(&config.architecture)?;
+    let host = host_architecture();
+    let multilib = (host, arch) == ("x64", "x86");
+    let custom_cc = matches!(config.toolchain, Some(Toolchain::Custom(_))) || env::var("CC").is_ok_and(|cc| !cc.is_empty());
+    if arch != host && !multilib && config.cross.is_none() && !custom_cc {
+        return Err(Error::config(
+            format!("architecture `{arch}` differs from the host (`{host}`) but no cross compiler is configured"),
+            &["architecture"],
+        )
+        .with_help("add a `cross:` section, or a `toolchain:` map naming a compiler for this architecture"));
+    }
+    let cpu = arch_triple.split('-').next().unwrap_or_default();
+    let arch_triple = match platform {
+        Platform::Linux => arch_triple.to_string(),
+        Platform::WindowsMingw | Platform::WindowsCmd => format!("{cpu}-w64-mingw32"),
+        Platform::Macos => format!("{cpu}-apple-darwin"),
+        Platform::Freebsd => format!("{cpu}-unknown-freebsd"),
+    };
+    let (cross, sysroot) = match &config.cross {
+        None => (None, None),
+        Some(cross) => {
+            let triple = cross.triple.clone().unwrap_or(arch_triple);
+            let triple_override = cross.triples.get(&triple);
+            if let Some(triple_override) = triple_override {
+                flags.cflags.extend(triple_override.cflags.iter().cloned());
+                flags.ldflags.extend(triple_override.ldflags.iter().cloned());
+            }
+            let prefix = triple_override
+                .and_then(|o| o.prefix.clone())
+                .or_else(|| cross.prefix.clone())
+                .unwrap_or_else(|| format!("{triple}-"));
+            let sysroot = triple_override.and_then(|o| o.sysroot.clone()).or_else(|| cross.sysroot.clone());
+            (Some((triple, prefix)), sysroot)
+        }
+    };
+
+    let assembler = match config.assembler.as_deref() {
+        None => None,
+        Some(assembler @ ("nasm" | "yasm")) => Some(assembler.to_string()),
+        Some(other) => {
+            return Err(Error::config(format!("unsupported assembler `{other}`"), &["assembler"])
+                .suggest(other, ["nasm", "yasm"]));
+        }
+    };
+    let bits = if arch == "x86" { "32" } else { "64" };
+    let asm_format = match (platform.is_windows(), platform == Platform::Macos, bits) {
+        (true, _, "32") => "win32",
+        (true, _, _) => "win64",
+        (_, true, "32") => "macho32",
+        (_, true, _) => "macho64",
+        (_, _, "32") => "elf32",
+        _ => "elf64",
+    };
+
+    let (cc, cxx, ar, ld) = tools(config, cross.as_ref())?;
+
+    let declared = declared_targets(config)?;
+    let order = dependency_order(&declared)?;
+
+    let mut targets: Vec<Target> = Vec::new();
+    for name in order {
+        let target = &declared[name];
+
+        let mut exclude = config.exclude.clone();
+        exclude.extend(target.exclude.iter().cloned());
+        let files: Vec<_> = files::resolve(target.files, &exclude, &target.key(name, "files"))?
+            .into_iter()
+            .filter(|f| Language::of(f).is_some())
+            .collect();
+        if files.is_empty() {
+            return Err(Error::config(
+                format!("no source files found for target `{name}`"),
+                &target.key(name, "files"),
+            ));
+        }
+        let sources: Vec<_> = object_paths(&files)
+            .into_iter()
+            .zip(files)
+            .map(|(object, path)| Source { language: Language::of(&path).unwrap(), path, object })
+            .collect();
+        if assembler.is_none() && sources.iter().any(|s| s.language == Language::Nasm) {
+            return Err(Error::config(format!("target `{name}` has .asm sources but no assembler is set"), &target.key(name, "files"))
+                .with_help("add `assembler: nasm` or `assembler: yasm`"));
+        }
+
+        let deps = transitive_deps(&declared, name);
+        let mut target_flags = flags.clone();
+        for dep in &deps {
+            target_flags.include_dirs.extend(declared[dep.as_str()].public_include_dirs.iter().cloned());
+        }
+        target_flags.include_dirs.extend(target.public_include_dirs.iter().cloned());
+        target_flags.extend(target.flags());
+
+        let link = match target.kind {
+            TargetKind::Static => Vec::new(),
+            _ => targets
+                .iter()
+                .rev()
+                .filter(|t| deps.contains(&t.name) && t.kind != TargetKind::Executable)
+                .map(|t| t.name.clone())
+                .collect(),
+        };
+
+        let cxx_deps = targets
+            .iter()
+            .any(|t| link.contains(&t.name) && t.kind == TargetKind::Static && t.linker == Language::Cxx);
+        let linker = if cxx_deps || sources.iter().any(|s| s.language == Language::Cxx) {
+            Language::Cxx
+        } else {
+            Language::C
+        };
+
+        let (output, implib) = output_name(name, target.kind, platform);
+        targets.push(Target {
+            name: name.to_string(),
+            kind: target.kind,
+            output,
+            implib,
+            sources,
+            linker,
+            flags: target_flags,
+            depends_on: target.depends_on.to_vec(),
+            link,
+        });
+    }
+
+    Ok(Project {
+        language: config.language.clone(),
+        cc,
+        cxx,
+        ar,
+        ld,
+        versions: Vec::new(),
+        c_std,
+        cxx_std,
+        assembler,
+        asm_format,
+        platform,
+        architecture: config.architecture.clone(),
+        march: march.to_string(),
+        triple: cross.map(|(triple, _)| triple),
+        sysroot,
+        model: model_name.to_string(),
+        model_chain: names,
+        model_origins: origins,
+        optimization,
+        root: root_from(&build_dir)?,
+        build_dir,
+        targets,
+    })
+}
+
+struct Declared<'a> {
+    top_level: bool,
+    kind: TargetKind,
+    files: &'a [String],
+    exclude: &'a [String],
+    depends_on: &'a [String],
+    public_include_dirs: &'a [String],
+    flags: Option<&'a TargetConfig>,
+}
+
+impl Declared<'_> {
+    fn flags(&self) -> Flags {
+        self.flags.map(TargetConfig::flags).unwrap_or_default()
+    }
+
+    fn key<'k>(&self, name: &'k str, field: &'k str) -> Vec<&'k str> {
+        if self.top_level { vec![field] } else { vec!["targets", name, field] }
+    }
+}
+
+pub fn check_target_name(name: &str, key: &[&str]) -> Result<()> {
+    if RESERVED_TARGETS.contains(&name) {
+        return Err(Error::config(format!("target name `{name}` is reserved"), key));
+    }
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(Error::config(format!("invalid target name `{name}`"), key)
+            .with_help("target names may only contain letters, digits, `_` and `-`"));
+    }
+    Ok(())
+}
+
+fn declared_targets(config: &Config) -> Result<BTreeMap<&str, Declared<'_>>> {
+    let mut declared = BTreeMap::new();
+
+    if let Some(target) = &config.target {
+        check_target_name(target, &["target"])?;
+        declared.insert(target.as_str(), Declared {
+            top_level: true,
+            kind: config.kind,
+            files: &config.files,
+            exclude: &[],
+            depends_on: &[],
+            public_include_dirs: &[],
+            flags: None,
+        });
+    }
+
+    for (name, target) in &config.targets {
+        check_target_name(name, &["targets", name])?;
+        if declared.contains_key(name.as_str()) {
+            return Err(Error::config(
+                format!("target `{name}` is declared both as `target` and in `targets`"),
+                &["targets", name],
+            ));
+        }
+        declared.insert(name.as_str(), Declared {
+            top_level: false,
+            kind: target.kind,
+            files: &target.files,
+            exclude: &target.exclude,
+            depends_on: &target.depends_on,
+            public_include_dirs: &target.public_include_dirs,
+            flags: Some(target),
+        });
+    }
+
+    if declared.is_empty() {
+        return Err(Error::config("no target declared", &[])
+            .with_help("add a `target` with `files`, or a `targets` map"));
+    }
+    Ok(declared)
+}
+
+fn dependency_order<'a>(declared: &BTreeMap<&'a str, Declared<'_>>) -> Result<Vec<&'a str>> {
+    fn visit<'a>(
+        name: &'a str,
+        declared: &BTreeMap<&'a str, Declared<'_>>,
+        visiting: &mut Vec<&'a str>,
+        order: &mut Vec<&'a str>,
+    ) -> Result<()> {
+        if order.contains(&name) {
+            return Ok(());
+        }
+        if let Some(start) = visiting.iter().position(|n| *n == name) {
+            let mut cycle = visiting[start..].to_vec();
+            cycle.push(name);
+            let last = visiting[visiting.len() - 1];
+            return Err(Error::config(
+                format!("dependency cycle between targets: {}", cycle.join(" -> ")),
+                &declared[last].key(last, "depends_on"),
+            ));
+        }
+
+        visiting.push(name);
+        for dep in declared[name].depends_on {
+            match declared.get_key_value(dep.as_str()) {
+                Some((dep, _)) => visit(dep, declared, visiting, order)?,
+                None => {
+                    return Err(Error::config(
+                        format!("target `{name}` depends on unknown target `{dep}`"),
+                        &declared[name].key(name, "depends_on"),
+                    )
+                    .suggest(dep, declared.keys().copied()));
+                }
+            }
+        }
+        visiting.pop();
+        order.push(name);
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    for name in declared.keys() {
+        visit(name, declared, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+fn transitive_deps(declared: &BTreeMap<&str, Declared<'_>>, name: &str) -> BTreeSet<String> {
+    let mut deps = BTreeSet::new();
+    let mut pending: Vec<_> = declared[name].depends_on.iter().collect();
+    while let Some(dep) = pending.pop() {
+        if deps.insert(dep.clone()) {
+            pending.extend(declared[dep.as_str()].depends_on);
+        }
+    }
+    deps
+}
+
+pub fn named_toolchain(name: &str) -> Option<(String, String)> {
+    let (base, version) = match name.split_once('-') {
+        Some((base, version)) if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit() || c == '.') => {
+            (base, format!("-{version}"))
+        }
+        _ => (name, String::new()),
+    };
+    match base {
+        "gcc" => Some((format!("gcc{version}"), format!("g++{version}"))),
+        "clang" => Some((format!("clang{version}"), format!("clang++{version}"))),
+        _ => None,
+    }
+}
+
+// Edit distance is useless on one- to three-letter names: `cpp` is closer to `c` than to `c++`.
+pub fn suggest_language(err: Error, value: &str) -> Error {
+    match LANGUAGE_ALIASES.iter().find(|(alias, _)| alias.eq_ignore_ascii_case(value)) {
+        Some((_, language)) => err.with_help(format!("did you mean `{language}`?")),
+        None => err.suggest(value, ["c", "c++"]),
+    }
+}
+
+pub fn host_architecture() -> &'static str {
+    match env::consts::ARCH {
+        "x86_64" => "x64",
+        "x86" => "x86",
+        "aarch64" => "arm64",
+        "arm" => "armv7",
+        other => other,
+    }
+}
+
+fn architecture(name: &str) -> Result<(&'static str, &'static str, &'static str)> {
+    let canonical = ARCHITECTURE_ALIASES.iter().find(|(alias, _)| *alias == name).map_or(name, |(_, arch)| *arch);
+    ARCHITECTURES
+        .iter()
+        .find(|(arch, _, _)| *arch == canonical)
+        .copied()
+        .ok_or_else(|| {
+            let names = ARCHITECTURES.iter().map(|(arch, _, _)| *arch);
+            Error::config(format!("unsupported architecture `{name}`"), &["architecture"])
+                .with_help(format!("expected one of: {}", names.clone().collect::<Vec<_>>().join(", ")))
+                .suggest(name, names)
+        })
+}
+
+fn tools(config: &Config, cross: Option<&(String, String)>) -> Result<(String, String, String, Option<String>)> {
+    let named = |name: &str| {
+        let (cc, cxx) = named_toolchain(name).ok_or_else(|| {
+            Error::config(format!("unknown toolchain `{name}`"), &["toolchain"])
+                .with_help("use gcc or clang, optionally with a version suffix like gcc-13, or a map of cc/cxx/ar/ld")
+        })?;
+        Ok::<_, Error>(match cross {
+            Some((triple, _)) if cc.starts_with("clang") => (format!("{cc} --target={triple}"), format!("{cxx} --target={triple}")),
+            Some((_, prefix)) => (format!("{prefix}{cc}"), format!("{prefix}{cxx}")),
+            None => (cc, cxx),
+        })
+    };
+    let default_ar = match cross {
+        Some((_, prefix)) => format!("{prefix}ar"),
+        None => "ar".to_string(),
+    };
+    if let Some(name) = &config.toolchain_override {
+        let (cc, cxx) = named(name)?;
+        return Ok((cc, cxx, default_ar, None));
+    }
+
+    let (mut cc, mut cxx, mut ar, ld) = match &config.toolchain {
+        None => {
+            let (cc, cxx) = named("gcc")?;
+            (cc, cxx, default_ar, None)
+        }
+        Some(Toolchain::Name(name)) => {
+            let (cc, cxx) = named(name)?;
+            (cc, cxx, default_ar, None)
+        }
+        Some(Toolchain::Custom(custom)) => {
+            let (default_cc, default_cxx) = named("gcc")?;
+            (
+                custom.cc.clone().unwrap_or(default_cc),
+                custom.cxx.clone().unwrap_or(default_cxx),
+                custom.ar.clone().unwrap_or(default_ar),
+                custom.ld.clone(),
+            )
+        }
+    };
+    // CI images often export a native CC; it must not replace the cross compilers.
+    for (var, tool) in [("CC", &mut cc), ("CXX", &mut cxx), ("AR", &mut ar)] {
+        if cross.is_none() && let Ok(value) = env::var(var) && !value.is_empty() {
+            *tool = value;
+        }
+    }
+    Ok((cc, cxx, ar, ld))
+}
+
+fn standard(language: &str, value: &Number, standards: &[&str], key: &str) -> Result<String> {
+    let value = value.to_string();
+    match standards.iter().find(|s| s.trim_start_matches('0') == value) {
+        Some(standard) => Ok(format!("{language}{standard}")),
+        None => Err(Error::config(format!("unsupported {language} standard `{value}`"), &[key])
+            .with_help(format!("expected one of: {}", standards.join(", ")))),
+    }
+}
+
+fn object_path(source: &str, keep_ext: bool) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for part in source.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => parts.push("__".to_string()),
+            part => parts.push(part.replace(':', "_")),
+        }
+    }
+
+    let file = parts.pop().unwrap_or_default();
+    let stem = match file.rsplit_once('.') {
+        Some((stem, _)) if !keep_ext => stem.to_string(),
+        _ => file,
+    };
+    parts.push(format!("{stem}.o"));
+    parts.join("/")
+}
+
+fn object_paths(sources: &[String]) -> Vec<String> {
+    let mut objects: Vec<_> = sources.iter().map(|s| object_path(s, false)).collect();
+
+    let mut counts = BTreeMap::new();
+    for object in &objects {
+        *counts.entry(object.clone()).or_insert(0) += 1;
+    }
+    for (object, source) in objects.iter_mut().zip(sources) {
+        if counts[object.as_str()] > 1 {
+            *object = object_path(source, true);
+        }
+    }
+
+    let mut seen = BTreeSet::new();
+    for object in &mut objects {
+        let stem = object.trim_end_matches(".o").to_string();
+        let mut n = 1;
+        while !seen.insert(object.clone()) {
+            *object = format!("{stem}-{n}.o");
+            n += 1;
+        }
+    }
+    objects
+}
+
+fn output_name(target: &str, kind: TargetKind, platform: Platform) -> (String, Option<String>) {
+    let windows = platform.is_windows();
+    let name = match kind {
+        TargetKind::Executable => target,
+        _ => target.strip_prefix("lib").unwrap_or(target),
+    };
+
+    match kind {
+        TargetKind::Executable if windows => (format!("{name}.exe"), None),
+        TargetKind::Executable => (name.to_string(), None),
+        TargetKind::Static => (format!("lib{name}.a"), None),
+        TargetKind::Shared if windows => (format!("{name}.dll"), Some(format!("lib{name}.dll.a"))),
+        TargetKind::Shared if platform == Platform::Macos => (format!("lib{name}.dylib"), None),
+        TargetKind::Shared => (format!("lib{name}.so"), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn objects(sources: &[&str]) -> Vec<String> {
+        object_paths(&sources.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn objects_mirror_source_paths() {
+        assert_eq!(objects(&["src/main.c", "src/net/tcp.cpp"]), ["src/main.o", "src/net/tcp.o"]);
+    }
+
+    #[test]
+    fn objects_outside_the_root_stay_inside_the_build_dir() {
+        assert_eq!(objects(&["../shared/log.c", "./main.c"]), ["__/shared/log.o", "main.o"]);
+        assert_eq!(objects(&["C:/src/main.c"]), ["C_/src/main.o"]);
+    }
+
+    #[test]
+    fn clashing_stems_keep_their_extension() {
+        assert_eq!(objects(&["src/util.c", "src/util.cpp", "src/main.c"]), ["src/util.c.o", "src/util.cpp.o", "src/main.o"]);
+    }
+
+    #[test]
+    fn remaining_clashes_get_a_counter() {
+        assert_eq!(objects(&["a.c", "a.cpp", "a.c.c"]), ["a.c.o", "a.cpp.o", "a.c-1.o"]);
+    }
+}
@@ -0,0 +1,330 @@
+use crate::config::{BuildConfig, Config, Defines, Platform, TargetConfig, TargetKind};
+use crate::project::{ARCHITECTURE_ALIASES, ARCHITECTURES, C_STANDARDS, CXX_STANDARDS};
+use serde_yaml::Number;
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+
+pub enum Json {
+    Bool(bool),
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+fn object<const N: usize>(entries: [(&str, Json); N]) -> Json {
+    Json::Object(entries.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+fn string(s: &str) -> Json {
+    Json::String(s.to_string())
+}
+
+fn strings(values: &[&str]) -> Json {
+    Json::Array(values.iter().map(|v| string(v)).collect())
+}
+
+fn of_type(name: &str) -> Json {
+    object([("type", string(name))])
+}
+
+pub trait Schema {
+    fn schema() -> Json;
+}
+
+impl Schema for String {
+    fn schema() -> Json {
+        of_type("string")
+    }
+}
+
+impl Schema for Number {
+    fn schema() -> Json {
+        of_type("number")
+    }
+}
+
+impl<T: Schema> Schema for Option<T> {
+    fn schema() -> Json {
+        T::schema()
+    }
+}
+
+impl<T: Schema> Schema for Vec<T> {
+    fn schema() -> Json {
+        object([("type", string("array")), ("items", T::schema())])
+    }
+}
+
+impl<T: Schema> Schema for BTreeMap<String, T> {
+    fn schema() -> Json {
+        object([("type", string("object")), ("additionalProperties", T::schema())])
+    }
+}
+
+impl Schema for TargetKind {
+    fn schema() -> Json {
+        object([("enum", strings(&["executable", "static", "shared"]))])
+    }
+}
+
+impl Schema for Defines {
+    fn schema() -> Json {
+        let value = object([("type", strings(&["string", "number", "boolean", "null"]))]);
+        object([(
+            "oneOf",
+            Json::Array(vec![
+                Vec::<String>::schema(),
+                object([("type", string("object")), ("additionalProperties", value)]),
+            ]),
+        )])
+    }
+}
+
+fn strict(required: &[&str], properties: Vec<(&str, Json)>) -> Json {
+    object([
+        ("type", string("object")),
+        ("required", strings(required)),
+        ("additionalProperties", Json::Bool(false)),
+        ("properties", Json::Object(properties.into_iter().map(|(k, v)| (k.to_string(), v)).collect())),
+    ])
+}
+
+fn flag_properties() -> Vec<(&'static str, Json)> {
+    vec![
+        ("include_dirs", Vec::<String>::schema()),
+        ("defines", Defines::schema()),
+        ("cflags", Vec::<String>::schema()),
+        ("asflags", Vec::<String>::schema()),
+        ("ldflags", Vec::<String>::schema()),
+        ("lib_dirs", Vec::<String>::schema()),
+        ("libs", Vec::<String>::schema()),
+        ("frameworks", Vec::<String>::schema()),
+        ("link_flags", Vec::<String>::schema()),
+    ]
+}
+
+impl Schema for BuildConfig {
+    fn schema() -> Json {
+        let mut properties = vec![
+            ("inherits", Option::<String>::schema()),
+            ("targetdir", Option::<String>::schema()),
+            ("optimization", Option::<Vec<String>>::schema()),
+        ];
+        properties.extend(flag_properties());
+        strict(&[], properties)
+    }
+}
+
+impl Schema for TargetConfig {
+    fn schema() -> Json {
+        let mut properties = vec![
+            ("type", TargetKind::schema()),
+            ("files", Vec::<String>::schema()),
+            ("exclude", Vec::<String>::schema()),
+            ("depends_on", Vec::<String>::schema()),
+            ("public_include_dirs", Vec::<String>::schema()),
+        ];
+        properties.extend(flag_properties());
+        strict(&[], properties)
+    }
+}
+
+fn numbers(values: &[&str]) -> Json {
+    Json::Array(values.iter().map(|v| Json::Number(v.parse().unwrap())).collect())
+}
+
+fn standards(language: &str, values: &[&str]) -> Json {
+    object([
+        ("if", object([("properties", object([("language", object([("const", string(language))]))]))])),
+        ("then", object([("properties", object([("standard", object([("enum", numbers(values))]))]))])),
+    ])
+}
+
+impl Schema for Config {
+    fn schema() -> Json {
+        let mut architectures: Vec<_> = ARCHITECTURES.iter().map(|(arch, _, _)| *arch).collect();
+        architectures.extend(ARCHITECTURE_ALIASES.iter().map(|(alias, _)| *alias));
+        let mut properties = vec![
+            ("language", object([("enum", strings(&["c", "c++"]))])),
+            ("standard", Number::schema()),
+            ("c_standard", object([("enum", numbers(C_STANDARDS))])),
+            ("cxx_standard", object([("enum", numbers(CXX_STANDARDS))])),
+            ("files", Vec::<String>::schema()),
+            ("exclude", Vec::<String>::schema()),
+            ("target", Option::<String>::schema()),
+            ("type", TargetKind::schema()),
+            ("targets", {
+                let mut targets = BTreeMap::<String, TargetConfig>::schema();
+                if let Json::Object(entries) = &mut targets {
+                    entries.push(("propertyNames".to_string(), object([("pattern", string("^[A-Za-z0-9_-]+$"))])));
+                }
+                targets
+            }),
+            ("platform", object([("enum", strings(&Platform::ALL.map(|(name, _)| name)))])),
+            ("architecture", object([("enum", strings(&architectures))])),
+            ("cross", strict(&[], vec![
+                ("triple", String::schema()),
+                ("prefix", String::schema()),
+                ("sysroot", String::schema()),
+                ("triples", object([
+                    ("type", string("object")),
+                    ("additionalProperties", strict(&[], vec![
+                        ("prefix", String::schema()),
+                        ("sysroot", String::schema()),
+                        ("cflags", Vec::<String>::schema()),
+                        ("ldflags", Vec::<String>::schema()),
+                    ])),
+                ])),
+            ])),
+            ("assembler", object([("enum", strings(&["nasm", "yasm"]))])),
+            ("toolchain", object([(
+                "oneOf",
+                Json::Array(vec![
+                    object([("type", string("string")), ("pattern", string("^(gcc|clang)(-[0-9.]+)?$"))]),
+                    strict(&[], ["cc", "cxx", "ar", "ld"].into_iter().map(|k| (k, String::schema())).collect()),
+                ]),
+            )])),
+        ];
+        properties.extend(flag_properties());
+        properties.push(("model", BTreeMap::<String, BuildConfig>::schema()));
+
+        let mut schema = vec![
+            ("$schema".to_string(), string("http://json-schema.org/draft-07/schema#")),
+            ("title".to_string(), string(".tr2make")),
+        ];
+        if let Json::Object(entries) = strict(&["language", "standard", "architecture"], properties) {
+            schema.extend(entries);
+        }
+        schema.push((
+            "allOf".to_string(),
+            Json::Array(vec![standards("c", C_STANDARDS), standards("c++", CXX_STANDARDS)]),
+        ));
+        Json::Object(schema)
+    }
+}
+
+impl Json {
+    fn write(&self, out: &mut String, indent: usize) -> fmt::Result {
+        let pad = "  ".repeat(indent + 1);
+        match self {
+            Json::Bool(b) => write!(out, "{b}"),
+            Json::Number(n) => write!(out, "{n}"),
+            Json::String(s) => write_string(out, s),
+            Json::Array(items) if items.is_empty() => out.write_str("[]"),
+            Json::Array(items) => {
+                out.write_str("[\n")?;
+                for (i, item) in items.iter().enumerate() {
+                    out.write_str(&pad)?;
+                    item.write(out, indent + 1)?;
+                    out.write_str(if i + 1 < items.len() { ",\n" } else { "\n" })?;
+                }
+                write!(out, "{}]", "  ".repeat(indent))
+            }
+            Json::Object(entries) if entries.is_empty() => out.write_str("{}"),
+            Json::Object(entries) => {
+                out.write_str("{\n")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    out.write_str(&pad)?;
+                    write_string(out, key)?;
+                    out.write_str(": ")?;
+                    value.write(out, indent + 1)?;
+                    out.write_str(if i + 1 < entries.len() { ",\n" } else { "\n" })?;
+                }
+                write!(out, "{}}}", "  ".repeat(indent))
+            }
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = String::new();
+        self.write(&mut out, 0)?;
+        f.write_str(&out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Cross, CrossOverride, CustomToolchain};
+    use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+    use serde::forward_to_deserialize_any;
+
+    // Serde hands the field list of a derived struct to deserialize_struct; capture it and bail out.
+    struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> Deserializer<'de> for FieldNames<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _: &'static str,
+            fields: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("fields captured"))
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf option unit
+            unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    fn fields<T: DeserializeOwned>() -> Vec<&'static str> {
+        let mut fields: &'static [&'static str] = &[];
+        let _ = T::deserialize(FieldNames(&mut fields));
+        let mut fields = fields.to_vec();
+        fields.sort();
+        fields
+    }
+
+    fn get<'a>(json: &'a Json, key: &str) -> &'a Json {
+        match json {
+            Json::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v).unwrap(),
+            _ => panic!("`{key}` looked up in a non-object"),
+        }
+    }
+
+    fn properties(json: &Json) -> Vec<&str> {
+        let Json::Object(entries) = get(json, "properties") else { panic!("properties is not an object") };
+        let mut names: Vec<_> = entries.iter().map(|(k, _)| k.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn schema_lists_every_config_field() {
+        let config = Config::schema();
+        let cross = get(get(&config, "properties"), "cross");
+        let Json::Array(toolchains) = get(get(get(&config, "properties"), "toolchain"), "oneOf") else { panic!() };
+
+        assert_eq!(properties(&config), fields::<Config>());
+        assert_eq!(properties(&TargetConfig::schema()), fields::<TargetConfig>());
+        assert_eq!(properties(&BuildConfig::schema()), fields::<BuildConfig>());
+        assert_eq!(properties(cross), fields::<Cross>());
+        assert_eq!(properties(get(get(get(cross, "properties"), "triples"), "additionalProperties")), fields::<CrossOverride>());
+        assert_eq!(properties(&toolchains[1]), fields::<CustomToolchain>());
+    }
+}